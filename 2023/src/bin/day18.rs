@@ -3,10 +3,11 @@
 extern crate core;
 
 use advent_lib::direction::*;
+use advent_lib::geometry::point2;
+use advent_lib::lines::Polygon;
 use advent_lib::rgb::*;
 use advent_lib::*;
 use nom_parse_macros::parse_from;
-use Direction::*;
 
 #[parse_from(
     separated_list1(
@@ -47,22 +48,9 @@ fn dig_command_2(input: &Input) -> Vec<DigCommand> {
         .collect()
 }
 
-// Simplified from https://www.mathsisfun.com/geometry/area-irregular-polygons.html
 fn calculate_area(lines: &[DigCommand]) -> i64 {
-    let mut curr_height = 0i64;
-    let mut area = 1i64; // With the algorithm below, we always miss the start block
-    for DigCommand { direction, steps } in lines {
-        match direction {
-            North => {
-                area += steps; // One of the sides is always outside, so count the steps
-                curr_height -= steps;
-            }
-            East => area -= steps * curr_height,
-            South => curr_height += steps,
-            West => area += steps * (curr_height + 1),
-        }
-    }
-    area.abs()
+    let moves = lines.iter().map(|command| (command.direction, command.steps));
+    Polygon::from_moves(point2(0, 0), moves).enclosed_points()
 }
 
 fn calculate_part1(input: &Input) -> i64 { calculate_area(&dig_command_1(input)) }
@@ -2,6 +2,7 @@
 
 use advent_lib::geometry::{point2, BoundingBox, Point, Vector};
 use advent_lib::iter_utils::IteratorUtils;
+use advent_lib::lines::HalfLine;
 use advent_lib::parsing::parsable_pair;
 use advent_lib::parsing::separated_lines1;
 use advent_lib::*;
@@ -31,45 +32,16 @@ impl<const D: usize> Debug for Line<D> {
     }
 }
 
-impl Line<3> {
-    fn flatten(&self) -> Line<2> {
-        Line { p: self.p.switch_dimensions(), v: self.v.switch_dimensions() }
-    }
-}
-
-impl Line<2> {
-    // Based on answer here https://stackoverflow.com/questions/563198/how-do-you-detect-where-two-line-segments-intersect
-    // Adjusted to no longer require a division, so it's only integer calculations.
-    // p = self.p, q = other.p, r = self.v, s = other.v
-    fn intersects_in(&self, other: &Line<2>, min: Point<2, i128>, max: Point<2, i128>) -> bool {
-        let cross = self.v.cross(other.v);
-        if cross == 0 {
-            return false; // Parallel lines
-        }
-
-        let diff = other.p - self.p;
-
-        let t_times_cross = diff.cross(other.v);
-        if cross * t_times_cross < 0 {
-            return false; // Intersection is in the past for self
-        }
-
-        let u_times_cross = diff.cross(self.v);
-        if cross * u_times_cross < 0 {
-            return false; // Intersection is in the past for other
-        }
-
-        let intersect_times_cross = self.p * cross + self.v * t_times_cross;
-        BoundingBox::from(min * cross, max * cross).contains_inclusive(&intersect_times_cross)
-    }
-}
-
 fn calculate_part1(weather: &Weather) -> i128 {
-    let hail2d = weather.hail.iter().map(Line::flatten).collect::<Vec<_>>();
-    let (min, max) = if weather.hail.len() < 10 {
-        (point2(7, 7), point2(27, 27))
+    let hail2d = weather
+        .hail
+        .iter()
+        .map(|line| HalfLine::new(line.p.switch_dimensions(), line.v.switch_dimensions()))
+        .collect::<Vec<_>>();
+    let bounds = if weather.hail.len() < 10 {
+        BoundingBox::from(point2(7, 7), point2(27, 27))
     } else {
-        (
+        BoundingBox::from(
             point2(200000000000000, 200000000000000),
             point2(400000000000000, 400000000000000),
         )
@@ -78,7 +50,7 @@ fn calculate_part1(weather: &Weather) -> i128 {
     let mut count = 0;
     for ix in 0..hail2d.len() {
         for jx in (ix + 1)..hail2d.len() {
-            if hail2d[ix].intersects_in(&hail2d[jx], min, max) {
+            if hail2d[ix].intersection_within(&hail2d[jx], &bounds).is_some() {
                 count += 1;
             }
         }
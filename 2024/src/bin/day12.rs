@@ -1,43 +1,21 @@
 #![feature(test)]
 
-use advent_lib::direction::CardinalDirection::*;
-use advent_lib::direction::Direction::*;
-use advent_lib::grid::{Grid, Location};
-use advent_lib::iter_utils::{CountIf, SumWith};
+use advent_lib::grid::{Grid, Region};
+use advent_lib::iter_utils::SumWith;
 use advent_lib::*;
 use nom_parse_macros::parse_from;
 
-#[parse_from(Grid::parse)]
+#[parse_from(map(Grid::parse, |plot: Grid<char>| plot.detect_regions()))]
 struct Input {
-    plot: Grid<char>,
-    #[derived(plot.detect_regions())]
-    regions: Vec<Vec<Location>>,
-}
-
-impl Input {
-    fn sum_regions(&self, grid: &Grid<usize>) -> usize {
-        (&self.regions).sum_with(|reg| reg.len() * reg.sum_with(|&loc| grid[loc]))
-    }
+    regions: Vec<Region<char>>,
 }
 
 fn calculate_part1(input: &Input) -> usize {
-    let fences_grid = input.plot.map_entries(|location, current| {
-        (&[North, East, South, West]).count_if(|&&d| input.plot.get(location + d) != Some(current))
-    });
-
-    input.sum_regions(&fences_grid)
+    (&input.regions).sum_with(|region| region.area() * region.perimeter)
 }
 
 fn calculate_part2(input: &Input) -> usize {
-    let corners_grid = input.plot.map_entries(|location, current| {
-        (&[[N, NE, E], [E, SE, S], [S, SW, W], [W, NW, N]]).count_if(|dirs| {
-            let [forward, diagonal, side] =
-                dirs.map(|dir| input.plot.get(location + dir) == Some(current));
-            (forward && !diagonal && side) || (!forward && !side)
-        })
-    });
-
-    input.sum_regions(&corners_grid)
+    (&input.regions).sum_with(|region| region.area() * region.sides)
 }
 
 day_main!(Input);
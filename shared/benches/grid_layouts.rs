@@ -0,0 +1,89 @@
+//! Compares the row-major [Grid] with the [TiledGrid] and the [BorderedGrid] on a large grid. Run
+//! with `cargo bench --bench grid_layouts`.
+
+use advent_lib::geometry::point2;
+use advent_lib::grid::{BorderedGrid, Grid, TiledGrid};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SIZE: i32 = 2048;
+const RUNS: u32 = 5;
+
+/// A grid with large irregular areas of 4 values, generated with a xorshift so it doesn't need
+/// any extra dependencies.
+fn generate_grid() -> Grid<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut grid = Grid::new_default(0u8, SIZE, SIZE);
+    for (location, value) in grid.entries_mut() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let band = (location.x() / 37 + location.y() / 23) as u64;
+        *value = ((band + state.is_multiple_of(16) as u64) % 4) as u8;
+    }
+    grid
+}
+
+fn measure(name: &str, mut run: impl FnMut()) -> Duration {
+    run(); // Warm up
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        run();
+    }
+    let average = start.elapsed() / RUNS;
+    println!("{name:<28} {average:>12.2?}");
+    average
+}
+
+fn main() {
+    let grid = generate_grid();
+    let tiled = TiledGrid::from(&grid);
+    let bordered: BorderedGrid<u8> = grid.clone().with_border(u8::MAX);
+    println!("Grid of {SIZE}x{SIZE}, average of {RUNS} runs");
+
+    measure("row-major north_lines", || {
+        let sum = grid.north_lines().flatten().map(|(_, &v)| v as u64).sum::<u64>();
+        black_box(sum);
+    });
+    measure("tiled north_lines", || {
+        let sum = tiled.north_lines().flatten().map(|(_, &v)| v as u64).sum::<u64>();
+        black_box(sum);
+    });
+
+    measure("row-major fill", || {
+        let mut grid = grid.clone();
+        black_box(grid.fill(point2(SIZE / 2, SIZE / 2), 9));
+    });
+    measure("tiled fill", || {
+        let mut tiled = tiled.clone();
+        black_box(tiled.fill(point2(SIZE / 2, SIZE / 2), 9));
+    });
+    measure("bordered fill", || {
+        let mut bordered = bordered.clone();
+        black_box(bordered.fill(point2(SIZE / 2, SIZE / 2), 9));
+    });
+
+    measure("row-major neighbours", || {
+        let sum = grid
+            .locations()
+            .flat_map(|location| grid.direct_neighbours(location))
+            .map(|(_, &v)| v as u64)
+            .sum::<u64>();
+        black_box(sum);
+    });
+    measure("bordered neighbours", || {
+        let sum = grid
+            .locations()
+            .flat_map(|location| bordered.direct_neighbours(location))
+            .map(|(_, &v)| v as u64)
+            .sum::<u64>();
+        black_box(sum);
+    });
+
+    measure("row-major detect_regions", || {
+        black_box(grid.detect_regions().len());
+    });
+    measure("tiled detect_regions", || {
+        black_box(tiled.detect_regions().len());
+    });
+}
@@ -0,0 +1,338 @@
+//! Coordinate compression for sparse rectilinear geometry.
+//!
+//! When shapes are described by a few corners with coordinates in the hundreds of thousands, a
+//! full [Grid] would be way too large. Only the coordinates where something changes matter, so
+//! each axis is split at those breakpoints and every cell of the compressed grid represents a
+//! rectangle of real space.
+
+use crate::direction::Direction;
+use crate::geometry::{BoundingBox, Point, point2};
+use crate::grid::{Grid, Location};
+use crate::lines::LineSegment;
+use std::ops::Range;
+
+/// One axis of a [CompressedGrid]. Each breakpoint gets a cell of its own, and each gap between
+/// two breakpoints is merged into a single cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedAxis {
+    /// The real coordinate where each cell starts, followed by the (exclusive) end of the last
+    /// cell. Without any breakpoints this only holds a single value and the axis has no cells.
+    starts: Vec<i64>,
+}
+
+impl CompressedAxis {
+    /// Creates an axis for the given breakpoints. An extra cell is added on both sides, so the
+    /// outside of any shape that is drawn stays connected.
+    pub fn new(breakpoints: impl IntoIterator<Item = i64>) -> Self {
+        let mut breakpoints = breakpoints.into_iter().collect::<Vec<_>>();
+        breakpoints.sort_unstable();
+        breakpoints.dedup();
+        let (Some(&first), Some(&last)) = (breakpoints.first(), breakpoints.last()) else {
+            return CompressedAxis { starts: vec![0] };
+        };
+
+        let mut starts = Vec::with_capacity(breakpoints.len() * 2 + 3);
+        starts.push(first - 1);
+        for (ix, &breakpoint) in breakpoints.iter().enumerate() {
+            starts.push(breakpoint);
+            if breakpoints.get(ix + 1).is_none_or(|&next| next > breakpoint + 1) {
+                starts.push(breakpoint + 1);
+            }
+        }
+        starts.push(last + 2);
+        CompressedAxis { starts }
+    }
+
+    /// The number of cells on this axis
+    pub fn len(&self) -> usize { self.starts.len() - 1 }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// The index of the cell that contains the real coordinate
+    pub fn index_of(&self, value: i64) -> Option<usize> {
+        if value < self.starts[0] || value >= self.starts[self.len()] {
+            return None;
+        }
+        Some(self.starts.partition_point(|&start| start <= value) - 1)
+    }
+
+    /// The real coordinates covered by the cell
+    pub fn range(&self, index: usize) -> Range<i64> { self.starts[index]..self.starts[index + 1] }
+
+    /// The number of real coordinates covered by the cell
+    pub fn width(&self, index: usize) -> i64 { self.starts[index + 1] - self.starts[index] }
+}
+
+/// A [Grid] where each cell represents a rectangle of real space, as described by the
+/// [CompressedAxis] for x and y.
+#[derive(Clone)]
+pub struct CompressedGrid<T> {
+    x_axis: CompressedAxis,
+    y_axis: CompressedAxis,
+    grid: Grid<T>,
+}
+
+impl<T> CompressedGrid<T> {
+    pub fn new(
+        x_breakpoints: impl IntoIterator<Item = i64>,
+        y_breakpoints: impl IntoIterator<Item = i64>,
+        value: T,
+    ) -> Self
+    where
+        T: Clone,
+    {
+        let x_axis = CompressedAxis::new(x_breakpoints);
+        let y_axis = CompressedAxis::new(y_breakpoints);
+        let grid = Grid::new_default(value, x_axis.len() as i32, y_axis.len() as i32);
+        CompressedGrid { x_axis, y_axis, grid }
+    }
+
+    /// Creates a grid that uses the coordinates of all points as breakpoints
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Point<2, i64>>, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let (xs, ys): (Vec<_>, Vec<_>) = points.into_iter().map(|p| (p.x(), p.y())).unzip();
+        Self::new(xs, ys, value)
+    }
+
+    pub fn x_axis(&self) -> &CompressedAxis { &self.x_axis }
+
+    pub fn y_axis(&self) -> &CompressedAxis { &self.y_axis }
+
+    pub fn grid(&self) -> &Grid<T> { &self.grid }
+
+    pub fn grid_mut(&mut self) -> &mut Grid<T> { &mut self.grid }
+
+    /// The location in the compressed grid of the cell that contains the real point
+    pub fn location(&self, point: Point<2, i64>) -> Option<Location> {
+        let x = self.x_axis.index_of(point.x())?;
+        let y = self.y_axis.index_of(point.y())?;
+        Some(point2(x as i32, y as i32))
+    }
+
+    pub fn get(&self, point: Point<2, i64>) -> Option<&T> { self.grid.get(self.location(point)?) }
+
+    /// The real points (inclusive) covered by a cell of the compressed grid
+    pub fn cell_bounds(&self, location: Location) -> BoundingBox<2, i64> {
+        let x = self.x_axis.range(location.x() as usize);
+        let y = self.y_axis.range(location.y() as usize);
+        BoundingBox::from(point2(x.start, y.start), point2(x.end - 1, y.end - 1))
+    }
+
+    /// The number of real points covered by a cell of the compressed grid
+    pub fn cell_area(&self, location: Location) -> i64 {
+        self.x_axis.width(location.x() as usize) * self.y_axis.width(location.y() as usize)
+    }
+
+    /// The total number of real points covered by cells that match the predicate
+    pub fn area_where<F>(&self, predicate: F) -> i64
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.grid
+            .entries()
+            .filter(|(_, value)| predicate(value))
+            .map(|(location, _)| self.cell_area(location))
+            .sum()
+    }
+
+    /// Checks if all cells that overlap with the real bounds (inclusive) match the predicate
+    pub fn all_within<F>(&self, bounds: BoundingBox<2, i64>, predicate: F) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        let (Some(min), Some(max)) = (
+            self.location(bounds.min_point()),
+            self.location(bounds.max_point()),
+        ) else {
+            return false;
+        };
+        (min.y()..=max.y())
+            .all(|y| (min.x()..=max.x()).all(|x| predicate(&self.grid[point2(x, y)])))
+    }
+
+    /// Sets all cells that a horizontal or vertical line segment passes through
+    pub fn draw_segment(&mut self, segment: &LineSegment<2, i64>, value: T)
+    where
+        T: Clone,
+    {
+        assert!(
+            segment.is_horizontal() || segment.is_vertical(),
+            "Only horizontal and vertical lines can be drawn, found {segment:?}"
+        );
+        let start = self.location(segment.start).expect("Expect the start to be on the grid");
+        let end = self.location(segment.end).expect("Expect the end to be on the grid");
+        for y in start.y().min(end.y())..=start.y().max(end.y()) {
+            for x in start.x().min(end.x())..=start.x().max(end.x()) {
+                self.grid[point2(x, y)] = value.clone();
+            }
+        }
+    }
+
+    /// Draws the outline of a rectilinear polygon, where the last corner connects back to the
+    /// first.
+    pub fn draw_polygon(&mut self, corners: &[Point<2, i64>], value: T)
+    where
+        T: Clone,
+    {
+        for (ix, &start) in corners.iter().enumerate() {
+            let end = corners[(ix + 1) % corners.len()];
+            self.draw_segment(&LineSegment { start, end }, value.clone());
+        }
+    }
+
+    /// Replaces all connected cells that are equal to the cell at the real point with the new
+    /// value. Returns the real area that has been filled.
+    pub fn fill(&mut self, point: Point<2, i64>, value: T) -> i64
+    where
+        T: PartialEq + Clone,
+    {
+        let Some(start) = self.location(point) else {
+            return 0;
+        };
+        let replaced = self.grid[start].clone();
+        if replaced == value {
+            return 0;
+        }
+
+        let mut area = 0;
+        let mut stack = vec![start];
+        self.grid[start] = value.clone();
+        while let Some(location) = stack.pop() {
+            area += self.cell_area(location);
+            for dir in Direction::ALL {
+                let next = location + dir;
+                if let Some(cell) = self.grid.get_mut(next)
+                    && *cell == replaced
+                {
+                    *cell = value.clone();
+                    stack.push(next);
+                }
+            }
+        }
+        area
+    }
+
+    /// Fills everything outside of the drawn shapes, starting from the extra border cells
+    pub fn fill_outside(&mut self, value: T) -> i64
+    where
+        T: PartialEq + Clone,
+    {
+        if self.x_axis.is_empty() || self.y_axis.is_empty() {
+            return 0; // Nothing has been drawn, so there is no outside either
+        }
+        let corner = point2(self.x_axis.range(0).start, self.y_axis.range(0).start);
+        self.fill(corner, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompressedAxis, CompressedGrid};
+    use crate::geometry::{BoundingBox, Point, point2};
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    enum Tile {
+        Unknown,
+        Edge,
+        Outside,
+    }
+
+    fn lagoon() -> Vec<Point<2, i64>> {
+        [
+            (6, 0),
+            (6, 5),
+            (4, 5),
+            (4, 7),
+            (6, 7),
+            (6, 9),
+            (1, 9),
+            (1, 7),
+            (0, 7),
+            (0, 5),
+            (2, 5),
+            (2, 2),
+            (0, 2),
+            (0, 0),
+        ]
+        .map(|(x, y)| point2(x, y))
+        .to_vec()
+    }
+
+    #[test]
+    fn axis_cells() {
+        let axis = CompressedAxis::new([10, 3, 4, 10]);
+        assert_eq!(
+            vec![2..3, 3..4, 4..5, 5..10, 10..11, 11..12],
+            (0..axis.len()).map(|ix| axis.range(ix)).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(3), axis.index_of(7));
+        assert_eq!(None, axis.index_of(12));
+        assert_eq!(None, axis.index_of(1));
+
+        let empty = CompressedAxis::new([]);
+        assert!(empty.is_empty());
+        assert_eq!(None, empty.index_of(0));
+    }
+
+    #[test]
+    fn empty_grid() {
+        let mut grid = CompressedGrid::from_points(&[], Tile::Unknown);
+        assert!(grid.grid().is_empty());
+        assert_eq!(0, grid.fill_outside(Tile::Outside));
+        assert_eq!(None, grid.get(point2(0, 0)));
+    }
+
+    #[test]
+    fn dig_lagoon() {
+        let corners = lagoon();
+        let mut grid = CompressedGrid::from_points(&corners, Tile::Unknown);
+        grid.draw_polygon(&corners, Tile::Edge);
+        assert_eq!(38, grid.area_where(|&tile| tile == Tile::Edge));
+        grid.fill_outside(Tile::Outside);
+        assert_eq!(62, grid.area_where(|&tile| tile != Tile::Outside));
+    }
+
+    #[test]
+    fn scaled_up_lagoon() {
+        let corners: Vec<_> =
+            lagoon().iter().map(|p| point2(p.x() * 100_000, p.y() * 100_000)).collect();
+        let mut grid = CompressedGrid::from_points(&corners, Tile::Unknown);
+        grid.draw_polygon(&corners, Tile::Edge);
+        grid.fill_outside(Tile::Outside);
+        assert_eq!(
+            420_001_900_001,
+            grid.area_where(|&tile| tile != Tile::Outside)
+        );
+
+        let cell = grid.location(point2(150_000, 250_000)).unwrap();
+        let bounds = grid.cell_bounds(cell);
+        assert_eq!(point2(100_001, 200_001), bounds.min_point());
+        assert_eq!(point2(199_999, 499_999), bounds.max_point());
+        assert_eq!(99_999 * 299_999, grid.cell_area(cell));
+    }
+
+    #[test]
+    fn rectangle_inside_polygon() {
+        let corners = [
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]
+        .map(|(x, y)| point2(x, y));
+        let mut grid = CompressedGrid::from_points(&corners, Tile::Unknown);
+        grid.draw_polygon(&corners, Tile::Edge);
+        grid.fill_outside(Tile::Outside);
+
+        let inside = |a, b| grid.all_within(BoundingBox::from(a, b), |&t| t != Tile::Outside);
+        assert!(inside(point2(9, 5), point2(2, 3)));
+        assert!(!inside(point2(2, 5), point2(11, 1)));
+        assert_eq!(Some(&Tile::Unknown), grid.get(point2(8, 4)));
+    }
+}
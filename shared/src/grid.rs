@@ -1,7 +1,6 @@
 use crate::direction::Direction;
-use crate::geometry::{Point, PointIterator, Vector, point2, vector2};
+use crate::geometry::{Point, Vector, point2, vector2};
 use advent_macros::FromRepr;
-use bit_vec::BitVec;
 use image::{Rgba, RgbaImage};
 use nom::Err::Error;
 use nom::character::complete::{line_ending, not_line_ending};
@@ -14,10 +13,64 @@ use std::fmt::{Debug, Formatter, Write};
 use std::ops::{Add, Index, IndexMut, Range};
 use std::slice::{Iter, IterMut};
 
-#[derive(Clone, Hash, PartialEq)]
-pub struct Grid<T> {
+mod animation;
+mod automaton;
+mod bordered;
+mod coord;
+mod diff;
+mod distance;
+mod hashing;
+mod import;
+mod layout;
+mod line_mut;
+mod location_set;
+mod markers;
+mod oriented_search;
+mod pattern;
+mod rays;
+mod regions;
+mod resize;
+mod slide;
+mod tiled;
+mod windows;
+
+pub use animation::{AnimationFormat, GridRecorder};
+pub use automaton::{Automaton, Neighbours};
+pub use bordered::BorderedGrid;
+pub use coord::GridCoord;
+pub use diff::CellChange;
+pub use distance::Frontiers;
+pub use hashing::{CellGuard, HashedGrid};
+pub use layout::{GridLayout, LineIterator, LinesIterator};
+pub use line_mut::LineMut;
+pub use location_set::{LocationMap, LocationSet};
+pub use markers::{Markers, marked_grid_parser, uneven_marked_grid_parser};
+pub use oriented_search::{Heading, OrientedPath, OrientedSearch, Turn};
+pub use pattern::{Orientation, SequenceMatch, TemplateMatch};
+pub use rays::{Ray, reduced_step};
+pub use regions::Region;
+pub use tiled::TiledGrid;
+pub use windows::SummedAreaTable;
+
+/// A rectangular grid of cells, stored row by row. The coordinate type `C` is `i32` by default,
+/// but can be any [GridCoord] to match the other types used in a puzzle.
+///
+/// All methods in this module and the text parsing work for every coordinate type. These
+/// extensions are only available with the default `i32` coordinates:
+/// - regions ([Grid::detect_regions]), rays ([Grid::ray], [Grid::visible_from]) and distances
+///   ([Grid::distance_map], [Grid::frontiers])
+/// - windows ([Grid::summed_area_table], [Grid::window_max]) and patterns ([Grid::transform],
+///   [Grid::find_template])
+/// - markers ([Grid::take_markers], [Grid::take_letter_pairs]) and the image import
+///   ([Grid::from_image])
+/// - resizing ([Grid::grow], [Grid::padded], [Grid::tiled], [Grid::trimmed]) and the
+///   [BorderedGrid] and [TiledGrid] layouts
+/// - the mutable lines ([Grid::north_lines_mut] and friends), sliding ([Grid::slide]),
+///   [Grid::oriented_search], [Grid::diff], [Grid::state_hash] and the [Automaton]
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub struct Grid<T, C = i32> {
     items: Vec<T>,
-    size: Size,
+    size: Size<C>,
 }
 
 #[repr(u8)]
@@ -27,22 +80,24 @@ pub enum FillField {
     Filled = b'#',
 }
 
-pub type Location = Point<2, i32>;
+pub type Location<C = i32> = Point<2, C>;
 
-pub type Size = Vector<2, i32>;
+pub type Size<C = i32> = Vector<2, C>;
 
-impl From<(Size, usize)> for Location {
-    fn from(value: (Size, usize)) -> Self {
-        let index = value.1 as i32;
-        let x = index % value.0.x();
-        let y = index / value.0.x();
-        point2(x, y)
+impl<C: GridCoord> From<(Size<C>, usize)> for Location<C> {
+    fn from(value: (Size<C>, usize)) -> Self {
+        let width = value.0.x().to_index();
+        point2(
+            C::from_index(value.1 % width),
+            C::from_index(value.1 / width),
+        )
     }
 }
 
-impl<I, E, T> ParseFrom<I, E> for Grid<T>
+impl<I, E, T, C> ParseFrom<I, E> for Grid<T, C>
 where
     T: ParseFrom<I, E>,
+    C: GridCoord,
     E: ParseError<I>,
     I: AsBytes + Input,
     <I as Input>::Item: AsChar,
@@ -64,7 +119,7 @@ where
 
         Ok((
             rest,
-            Grid { items, size: vector2(width as i32, height as i32) },
+            Grid { items, size: vector2(C::from_index(width), C::from_index(height)) },
         ))
     }
 }
@@ -94,113 +149,78 @@ where
     Ok((rest, grid))
 }
 
-impl<T> Grid<T> {
-    pub fn new_empty(width: i32, height: i32) -> Grid<T>
+impl<T, C: GridCoord> Grid<T, C> {
+    pub fn new_empty(width: C, height: C) -> Grid<T, C>
     where
         T: Default + Clone,
     {
         Grid::new_default(T::default(), width, height)
     }
 
-    pub fn new_default(value: T, width: i32, height: i32) -> Grid<T>
+    pub fn new_default(value: T, width: C, height: C) -> Grid<T, C>
     where
         T: Clone,
     {
-        if width < 0 {
+        if width < C::default() {
             panic!("Width cannot be negative")
-        } else if height < 0 {
+        } else if height < C::default() {
             panic!("Height cannot be negative")
         }
         Grid {
-            items: vec![value.clone(); (width * height) as usize],
+            items: vec![value.clone(); (width * height).to_index()],
             size: vector2(width, height),
         }
     }
 
-    pub fn height(&self) -> i32 { self.size.y() }
+    pub fn height(&self) -> C { self.size.y() }
 
-    pub fn width(&self) -> i32 { self.size.x() }
+    pub fn width(&self) -> C { self.size.x() }
 
-    pub fn size(&self) -> Vector<2, i32> { self.size }
+    pub fn size(&self) -> Size<C> { self.size }
 
     pub fn len(&self) -> usize { self.items.len() }
 
     pub fn is_empty(&self) -> bool { self.items.is_empty() }
 
-    pub fn x_range(&self) -> Range<i32> { 0..self.size.x() }
+    pub fn x_range(&self) -> Range<C> { C::default()..self.size.x() }
 
-    pub fn y_range(&self) -> Range<i32> { 0..self.size.y() }
+    pub fn y_range(&self) -> Range<C> { C::default()..self.size.y() }
 
-    pub fn is_valid_location(&self, location: &Location) -> bool {
+    pub fn is_valid_location(&self, location: &Location<C>) -> bool {
         self.x_range().contains(&location.x()) && self.y_range().contains(&location.y())
     }
 
-    fn index_from_location(&self, location: Location) -> Option<usize> {
+    fn index_from_location(&self, location: Location<C>) -> Option<usize> {
         if self.is_valid_location(&location) {
-            Some((location.x() + location.y() * self.width()) as usize)
+            Some((location.x() + location.y() * self.width()).to_index())
         } else {
             None
         }
     }
 
-    pub fn direct_neighbours(&self, location: Location) -> impl Iterator<Item = (Direction, &T)> {
-        Direction::ALL
-            .into_iter()
-            .flat_map(move |dir| self.get(location + dir).map(|p| (dir, p)))
+    /// The location one step in the direction, if that is still on the grid
+    pub fn neighbour(&self, location: Location<C>, direction: Direction) -> Option<Location<C>> {
+        layout::neighbour(self, location, direction)
     }
 
-    pub fn cardinal_neighbours(&self, location: Location) -> Option<[&T; 8]>
-    where
-        T: Default + Copy,
-    {
-        // Check boundaries, we don't support fetching the neighbours at the edge
-        if location.x() <= 0
-            || location.x() >= self.width()
-            || location.y() <= 0
-            || location.y() >= self.height()
-        {
-            return None;
-        }
-
-        let ix = (location.x() + location.y() * self.width()) as usize;
-        let y_step = self.width() as usize;
-
-        // We've checked the bounds
-        unsafe {
-            Some([
-                self.items.get_unchecked(ix - y_step),
-                self.items.get_unchecked(ix - y_step + 1),
-                self.items.get_unchecked(ix + 1),
-                self.items.get_unchecked(ix + y_step + 1),
-                self.items.get_unchecked(ix + y_step),
-                self.items.get_unchecked(ix + y_step - 1),
-                self.items.get_unchecked(ix - 1),
-                self.items.get_unchecked(ix - y_step - 1),
-            ])
-        }
+    pub fn direct_neighbours(
+        &self,
+        location: Location<C>,
+    ) -> impl Iterator<Item = (Direction, &T)> {
+        layout::direct_neighbours(self, location)
     }
 
-    pub fn get(&self, location: Location) -> Option<&T> {
+    pub fn get(&self, location: Location<C>) -> Option<&T> {
         let ix = self.index_from_location(location)?;
         self.items.get(ix)
     }
 
-    fn index_from_location_infinite(&self, location: Location) -> usize {
-        let x = location.x().rem_euclid(self.width()) as usize;
-        let y = location.y().rem_euclid(self.height()) as usize;
-        y * self.width() as usize + x
-    }
-
-    pub fn get_infinite(&self, location: Location) -> &T {
-        self.items.get(self.index_from_location_infinite(location)).unwrap()
-    }
-
-    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> {
+    pub fn get_mut(&mut self, location: Location<C>) -> Option<&mut T> {
         let ix = self.index_from_location(location)?;
         self.items.get_mut(ix)
     }
 
-    pub fn swap(&mut self, first: Location, second: Location) {
+    pub fn swap(&mut self, first: Location<C>, second: Location<C>) {
         if first == second {
             return; // Nothing to swap
         }
@@ -212,30 +232,29 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn locations(&self) -> impl Iterator<Item = Location> {
-        let ys = self.y_range();
-        let xs = self.x_range();
-
-        ys.flat_map(move |y| xs.clone().map(move |x| point2(x, y)))
+    pub fn locations(&self) -> impl Iterator<Item = Location<C>> + use<T, C> {
+        layout::row_order(self.size)
     }
 
-    pub fn locations_where<F>(&self, predicate: F) -> impl Iterator<Item = Location>
+    pub fn locations_where<F>(&self, predicate: F) -> impl Iterator<Item = Location<C>>
     where
         F: Fn(&T) -> bool,
     {
         self.entries().filter(move |(_, field)| predicate(*field)).map(|(loc, _)| loc)
     }
 
-    pub fn entries(&self) -> Indexed<Iter<'_, T>> { Indexed::new(self.items.iter(), self.width()) }
+    pub fn entries(&self) -> Indexed<Iter<'_, T>, C> {
+        Indexed::new(self.items.iter(), self.width())
+    }
 
-    pub fn entries_mut(&mut self) -> Indexed<IterMut<'_, T>> {
+    pub fn entries_mut(&mut self) -> Indexed<IterMut<'_, T>, C> {
         let width = self.width();
         Indexed::new(self.items.iter_mut(), width)
     }
 
     pub fn values(&self) -> impl Iterator<Item = &T> { self.items.iter() }
 
-    pub fn map<U, F>(&self, function: F) -> Grid<U>
+    pub fn map<U, F>(&self, function: F) -> Grid<U, C>
     where
         F: FnMut(&T) -> U,
     {
@@ -244,20 +263,18 @@ impl<T> Grid<T> {
         Grid { items, size: self.size }
     }
 
-    pub fn map_entries<U, F>(&self, function: F) -> Grid<U>
+    pub fn map_entries<U, F>(&self, function: F) -> Grid<U, C>
     where
-        F: Fn(Location, &T) -> U,
+        F: Fn(Location<C>, &T) -> U,
     {
         let mut items = Vec::with_capacity(self.items.len());
-        self.items
-            .iter()
-            .enumerate()
-            .map(|(ix, value)| function((self.size, ix).into(), value))
+        self.entries()
+            .map(|(location, value)| function(location, value))
             .for_each(|result| items.push(result));
         Grid { items, size: self.size }
     }
 
-    pub fn find<F>(&self, mut predicate: F) -> Option<Location>
+    pub fn find<F>(&self, mut predicate: F) -> Option<Location<C>>
     where
         T: PartialEq,
         F: FnMut(&T) -> bool,
@@ -266,69 +283,95 @@ impl<T> Grid<T> {
         Some((self.size, index).into())
     }
 
+    pub fn search_graph<'a, FS, FH, S>(
+        &'a self,
+        goal: Location<C>,
+        score_step: FS,
+        heuristic_score: FH,
+    ) -> GridGraph<'a, T, FS, FH, C>
+    where
+        FS: Fn(Location<C>, &T, &T) -> Option<S>,
+        S: Copy + Default + Eq + Add<S, Output = S> + Ord,
+        FH: Fn(Vector<2, C::Delta>) -> S,
+    {
+        GridGraph { grid: self, goal, score_step, heuristic_score }
+    }
+
+    pub fn cardinal_neighbours(&self, location: Location<C>) -> Option<[&T; 8]>
+    where
+        T: Default + Copy,
+    {
+        // Check boundaries, we don't support fetching the neighbours at the edge
+        if location.x() <= C::default()
+            || location.x() >= self.width()
+            || location.y() <= C::default()
+            || location.y() >= self.height()
+        {
+            return None;
+        }
+
+        let ix = (location.x() + location.y() * self.width()).to_index();
+        let y_step = self.width().to_index();
+
+        // We've checked the bounds
+        unsafe {
+            Some([
+                self.items.get_unchecked(ix - y_step),
+                self.items.get_unchecked(ix - y_step + 1),
+                self.items.get_unchecked(ix + 1),
+                self.items.get_unchecked(ix + y_step + 1),
+                self.items.get_unchecked(ix + y_step),
+                self.items.get_unchecked(ix + y_step - 1),
+                self.items.get_unchecked(ix - 1),
+                self.items.get_unchecked(ix - y_step - 1),
+            ])
+        }
+    }
+
+    fn index_from_location_infinite(&self, location: Location<C>) -> usize {
+        let x = location.x().wrap(self.width());
+        let y = location.y().wrap(self.height());
+        (x + y * self.width()).to_index()
+    }
+
+    pub fn get_infinite(&self, location: Location<C>) -> &T {
+        self.items.get(self.index_from_location_infinite(location)).unwrap()
+    }
+
     /// # Safety
     ///
     /// This method does not do any boundary checks, so only use this if you already know that
     /// x and y are within boundary (e.g. coming directly from the x_range and y_range).
-    pub unsafe fn get_unchecked(&self, x: i32, y: i32) -> &T {
-        unsafe { self.items.get_unchecked((x + y * self.width()) as usize) }
+    pub unsafe fn get_unchecked(&self, x: C, y: C) -> &T {
+        unsafe { self.items.get_unchecked((x + y * self.width()).to_index()) }
     }
 
     /// # Safety
     ///
     /// This method does not do any boundary checks, so only use this if you already know that
     /// x and y are within boundary (e.g. coming directly from the x_range and y_range).
-    pub unsafe fn get_unchecked_mut(&mut self, x: i32, y: i32) -> &mut T {
+    pub unsafe fn get_unchecked_mut(&mut self, x: C, y: C) -> &mut T {
         let width = self.width();
-        unsafe { self.items.get_unchecked_mut((x + y * width) as usize) }
+        unsafe { self.items.get_unchecked_mut((x + y * width).to_index()) }
     }
 
-    pub fn north_line<'a>(&'a self, x: i32) -> LineIterator<'a, T> {
-        LineIterator::North { grid: self, x, y: self.height() - 1 }
-    }
+    pub fn north_line(&self, x: C) -> LineIterator<'_, Self> { LineIterator::north(self, x) }
 
-    pub fn east_line<'a>(&'a self, y: i32) -> LineIterator<'a, T> {
-        LineIterator::East { grid: self, x: 0, y }
-    }
+    pub fn east_line(&self, y: C) -> LineIterator<'_, Self> { LineIterator::east(self, y) }
 
-    pub fn south_line<'a>(&'a self, x: i32) -> LineIterator<'a, T> {
-        LineIterator::South { grid: self, x, y: 0 }
-    }
+    pub fn south_line(&self, x: C) -> LineIterator<'_, Self> { LineIterator::south(self, x) }
 
-    pub fn west_line<'a>(&'a self, y: i32) -> LineIterator<'a, T> {
-        LineIterator::West { grid: self, x: self.width() - 1, y }
-    }
+    pub fn west_line(&self, y: C) -> LineIterator<'_, Self> { LineIterator::west(self, y) }
 
-    pub fn north_lines<'a>(&'a self) -> LinesIterator<'a, T> {
-        LinesIterator::North { grid: self, x: 0 }
-    }
+    pub fn north_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::north(self) }
 
-    pub fn east_lines<'a>(&'a self) -> LinesIterator<'a, T> {
-        LinesIterator::East { grid: self, y: 0 }
-    }
+    pub fn east_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::east(self) }
 
-    pub fn south_lines<'a>(&'a self) -> LinesIterator<'a, T> {
-        LinesIterator::South { grid: self, x: 0 }
-    }
+    pub fn south_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::south(self) }
 
-    pub fn west_lines<'a>(&'a self) -> LinesIterator<'a, T> {
-        LinesIterator::West { grid: self, y: 0 }
-    }
+    pub fn west_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::west(self) }
 
-    pub fn mut_line<F>(&mut self, start: Location, direction: Vector<2, i32>, function: F)
-    where
-        F: Fn(&mut T),
-    {
-        for loc in PointIterator::new(start, direction) {
-            if let Some(value) = self.get_mut(loc) {
-                function(value);
-            } else {
-                break;
-            }
-        }
-    }
-
-    pub fn sub_grid(&self, from_x_range: Range<i32>, from_y_range: Range<i32>) -> Grid<T>
+    pub fn sub_grid(&self, from_x_range: Range<C>, from_y_range: Range<C>) -> Grid<T, C>
     where
         T: Default + Clone,
     {
@@ -336,8 +379,8 @@ impl<T> Grid<T> {
             from_x_range.end - from_x_range.start,
             from_y_range.end - from_y_range.start,
         );
-        for y in from_y_range.clone() {
-            for x in from_x_range.clone() {
+        for y in coord::coords(from_y_range.clone()) {
+            for x in coord::coords(from_x_range.clone()) {
                 if let Some(cell) = self.get(point2(x, y)) {
                     let target_x = x - from_x_range.start;
                     let target_y = y - from_y_range.start;
@@ -352,78 +395,40 @@ impl<T> Grid<T> {
         grid
     }
 
-    pub fn fill(&mut self, start: Location, value: T) -> usize
+    pub fn fill(&mut self, start: Location<C>, value: T) -> usize
     where
         T: PartialEq + Clone,
     {
-        let accept_cell = if let Some(cell) = self.get(start) {
-            cell.clone()
-        } else {
-            return 0; // Not in range, just return
-        };
-
-        let mut stack = Vec::with_capacity(self.height() as usize);
-        stack.push(start);
-        let mut count_cells = 0usize;
-
-        while !stack.is_empty() {
-            let [x, y] = stack.pop().unwrap().coords;
-            let mut lx = x;
-            while lx > 0 {
-                // Safety: we know that (x,y) is in bounds and we check lx explicitly
-                let fill_cell = unsafe { self.get_unchecked_mut(lx - 1, y) };
-                if *fill_cell == accept_cell {
-                    *fill_cell = value.clone();
-                    count_cells += 1;
-                    lx -= 1;
-                } else {
-                    break;
-                }
-            }
-
-            let mut rx = x;
-            while rx < self.width() {
-                // Safety: we know that (x,y) is in bounds and we check rx explicitly
-                let fill_cell = unsafe { self.get_unchecked_mut(rx, y) };
-                if *fill_cell == accept_cell {
-                    *fill_cell = value.clone();
-                    count_cells += 1;
-                    rx += 1;
-                } else {
-                    break;
-                }
-            }
+        layout::fill(self, start, value)
+    }
 
-            let mut scan_next_fill = |y| {
-                let mut span_added = false;
-                for x in lx..rx {
-                    let check_cell = unsafe { self.get_unchecked(x, y) };
-                    if *check_cell != accept_cell {
-                        span_added = false;
-                    } else if !span_added {
-                        stack.push(point2(x, y));
-                        span_added = true;
-                    }
-                }
+    /// Calls the function for each cell from the start in steps of the direction, until it
+    /// leaves the grid. The direction uses the signed [GridCoord::Delta], so it can also go
+    /// towards 0 with unsigned coordinates.
+    pub fn mut_line<F>(&mut self, start: Location<C>, direction: Vector<2, C::Delta>, function: F)
+    where
+        F: Fn(&mut T),
+    {
+        let mut next = Some(start);
+        while let Some(location) = next {
+            let Some(value) = self.get_mut(location) else {
+                break;
             };
-
-            if y > 0 {
-                scan_next_fill(y - 1);
-            }
-            if y < self.height() - 1 {
-                scan_next_fill(y + 1);
-            }
+            function(value);
+            next = location
+                .x()
+                .checked_offset(direction.x())
+                .zip(location.y().checked_offset(direction.y()))
+                .map(|(x, y)| point2(x, y));
         }
-
-        count_cells
     }
 
     pub fn draw_with_overlay<'a, I>(&self, locations: I, c: char) -> String
     where
-        I: IntoIterator<Item = &'a Location>,
+        I: IntoIterator<Item = &'a Location<C>>,
         T: Into<char> + Copy,
     {
-        let mut char_grid: Grid<char> = self.map(|b| (*b).into());
+        let mut char_grid: Grid<char, C> = self.map(|b| (*b).into());
         for loc in locations {
             if let Some(cell) = char_grid.get_mut(*loc) {
                 *cell = c;
@@ -433,91 +438,51 @@ impl<T> Grid<T> {
         format!("{char_grid:?}")
     }
 
-    pub fn detect_regions(&self) -> Vec<Vec<Location>>
-    where
-        T: Eq + Clone,
-    {
-        let mut regions = Vec::new();
-        let mut visited = BitVec::from_elem(self.items.len(), false);
-        for start in self.locations() {
-            let start_ix = self.index_from_location(start).unwrap();
-            if visited[start_ix] {
-                continue;
-            }
-            let current_value = self.items[start_ix].clone();
-
-            let mut region = Vec::new();
-            let mut stack = vec![start];
-            while let Some(location) = stack.pop() {
-                let location_ix = self.index_from_location(location).unwrap();
-                if visited[location_ix] {
-                    continue;
-                }
-                visited.set(location_ix, true);
-                region.push(location);
-                for d in Direction::ALL {
-                    let neighbour = location + d;
-                    if let Some(neighbour_ix) = self.index_from_location(neighbour) {
-                        unsafe {
-                            if self.items.get_unchecked(neighbour_ix).clone() == current_value
-                                && !visited.get_unchecked(neighbour_ix)
-                            {
-                                stack.push(neighbour);
-                            }
-                        }
-                    }
-                }
-            }
-            regions.push(region);
-        }
-
-        regions
-    }
-
     pub fn render_to_image(&self, filename: &str, mapping: impl Fn(&T) -> [u8; 4]) {
-        let mut image = RgbaImage::new(self.width() as u32, self.height() as u32);
-        self.entries().for_each(|(loc, val)| {
-            let pixel = image.get_pixel_mut(loc.x() as u32, loc.y() as u32);
-            *pixel = Rgba(mapping(val));
-        });
-        image
+        self.to_image(1, mapping)
             .save_with_format(filename, image::ImageFormat::Png)
             .expect("Expect saving to not be a problem");
     }
 
-    pub fn search_graph<'a, FS, FH, S>(
-        &'a self,
-        goal: Location,
-        score_step: FS,
-        heuristic_score: FH,
-    ) -> GridGraph<'a, T, FS, FH>
-    where
-        FS: Fn(Location, &T, &T) -> Option<S>,
-        S: Copy + Default + Eq + Add<S, Output = S> + Ord,
-        FH: Fn(Vector<2, i32>) -> S,
-    {
-        GridGraph::<'a, T, FS, FH> { grid: self, goal, score_step, heuristic_score }
+    /// Creates an image where each cell is drawn as a square of `scale` by `scale` pixels
+    pub fn to_image(&self, scale: u32, mapping: impl Fn(&T) -> [u8; 4]) -> RgbaImage {
+        let (width, height) = (
+            self.width().to_index() as u32,
+            self.height().to_index() as u32,
+        );
+        let mut image = RgbaImage::new(width * scale, height * scale);
+        self.entries().for_each(|(loc, val)| {
+            let color = Rgba(mapping(val));
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let x = loc.x().to_index() as u32 * scale + dx;
+                    let y = loc.y().to_index() as u32 * scale + dy;
+                    *image.get_pixel_mut(x, y) = color;
+                }
+            }
+        });
+        image
     }
 }
 
-impl<T: Copy + Into<char>> Debug for Grid<T> {
+impl<T: Copy + Into<char>, C: GridCoord> Debug for Grid<T, C> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (width, height) = (self.width().to_index(), self.height().to_index());
         f.write_str("Grid(")?;
-        f.write_str(&format!("{}x{}", self.width(), self.height()))?;
+        f.write_str(&format!("{width}x{height}"))?;
         f.write_char(')')?;
         f.write_char('\n')?;
 
         f.write_char('┌')?;
-        for _ in self.x_range() {
+        for _ in 0..width {
             f.write_char('─')?;
         }
         f.write_char('┐')?;
         f.write_char('\n')?;
 
-        for y in self.y_range() {
+        for y in 0..height {
             f.write_char('│')?;
-            for x in self.x_range() {
-                let item = self.get(point2(x, y)).unwrap();
+            for item in &self.items[y * width..(y + 1) * width] {
                 f.write_char((*item).into())?;
             }
             f.write_char('│')?;
@@ -525,7 +490,7 @@ impl<T: Copy + Into<char>> Debug for Grid<T> {
         }
 
         f.write_char('└')?;
-        for _ in self.x_range() {
+        for _ in 0..width {
             f.write_char('─')?;
         }
         f.write_char('┘')?;
@@ -535,132 +500,37 @@ impl<T: Copy + Into<char>> Debug for Grid<T> {
     }
 }
 
-impl<T> Index<Location> for Grid<T> {
+impl<T, C: GridCoord> Index<Location<C>> for Grid<T, C> {
     type Output = T;
 
-    fn index(&self, index: Location) -> &Self::Output { self.get(index).unwrap() }
+    fn index(&self, index: Location<C>) -> &Self::Output { self.get(index).unwrap() }
 }
 
-impl<T> IndexMut<Location> for Grid<T> {
-    fn index_mut(&mut self, index: Location) -> &mut Self::Output { self.get_mut(index).unwrap() }
+impl<T, C: GridCoord> IndexMut<Location<C>> for Grid<T, C> {
+    fn index_mut(&mut self, index: Location<C>) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
 }
 
-pub enum LineIterator<'a, T> {
-    North { grid: &'a Grid<T>, x: i32, y: i32 },
-    East { grid: &'a Grid<T>, x: i32, y: i32 },
-    South { grid: &'a Grid<T>, x: i32, y: i32 },
-    West { grid: &'a Grid<T>, x: i32, y: i32 },
-}
+impl<T, C: GridCoord> GridLayout for Grid<T, C> {
+    type Cell = T;
+    type Coord = C;
 
-impl<'a, T> Iterator for LineIterator<'a, T> {
-    type Item = (Location, &'a T);
+    fn layout_size(&self) -> Size<C> { self.size }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            LineIterator::North { grid, x, y } => {
-                if *y >= 0 {
-                    let curr_y = *y;
-                    *y -= 1;
-                    Some((point2(*x, curr_y), unsafe {
-                        grid.get_unchecked(*x, curr_y)
-                    }))
-                } else {
-                    None
-                }
-            }
-            LineIterator::East { grid, x, y } => {
-                if *x < grid.width() {
-                    let curr_x = *x;
-                    *x += 1;
-                    Some((point2(curr_x, *y), unsafe {
-                        grid.get_unchecked(curr_x, *y)
-                    }))
-                } else {
-                    None
-                }
-            }
-            LineIterator::South { grid, x, y } => {
-                if *y < grid.height() {
-                    let curr_y = *y;
-                    *y += 1;
-                    Some((point2(*x, curr_y), unsafe {
-                        grid.get_unchecked(*x, curr_y)
-                    }))
-                } else {
-                    None
-                }
-            }
-            LineIterator::West { grid, x, y } => {
-                if *x >= 0 {
-                    let curr_x = *x;
-                    *x -= 1;
-                    Some((point2(curr_x, *y), unsafe {
-                        grid.get_unchecked(curr_x, *y)
-                    }))
-                } else {
-                    None
-                }
-            }
-        }
+    unsafe fn cell_unchecked(&self, location: Location<C>) -> &T {
+        unsafe { self.get_unchecked(location.x(), location.y()) }
     }
-}
-
-pub enum LinesIterator<'a, T> {
-    North { grid: &'a Grid<T>, x: i32 },
-    East { grid: &'a Grid<T>, y: i32 },
-    South { grid: &'a Grid<T>, x: i32 },
-    West { grid: &'a Grid<T>, y: i32 },
-}
-
-impl<'a, T> Iterator for LinesIterator<'a, T> {
-    type Item = LineIterator<'a, T>;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            LinesIterator::North { grid, x } => {
-                if *x < grid.width() {
-                    let curr_x = *x;
-                    *x += 1;
-                    Some(LineIterator::North { grid, x: curr_x, y: grid.height() - 1 })
-                } else {
-                    None
-                }
-            }
-            LinesIterator::East { grid, y } => {
-                if *y < grid.height() {
-                    let curr_y = *y;
-                    *y += 1;
-                    Some(LineIterator::East { grid, x: 0, y: curr_y })
-                } else {
-                    None
-                }
-            }
-            LinesIterator::South { grid, x } => {
-                if *x < grid.width() {
-                    let curr_x = *x;
-                    *x += 1;
-                    Some(LineIterator::South { grid, x: curr_x, y: 0 })
-                } else {
-                    None
-                }
-            }
-            LinesIterator::West { grid, y } => {
-                if *y < grid.height() {
-                    let curr_y = *y;
-                    *y += 1;
-                    Some(LineIterator::West { grid, x: grid.width() - 1, y: curr_y })
-                } else {
-                    None
-                }
-            }
-        }
+    unsafe fn cell_unchecked_mut(&mut self, location: Location<C>) -> &mut T {
+        unsafe { self.get_unchecked_mut(location.x(), location.y()) }
     }
 }
 
-impl<'a, T> IntoIterator for &'a Grid<T> {
-    type Item = (Location, &'a T);
+impl<'a, T, C: GridCoord> IntoIterator for &'a Grid<T, C> {
+    type Item = (Location<C>, &'a T);
 
-    type IntoIter = Indexed<Iter<'a, T>>;
+    type IntoIter = Indexed<Iter<'a, T>, C>;
 
     fn into_iter(self) -> Self::IntoIter { self.entries() }
 }
@@ -730,31 +600,34 @@ mod tests {
 }
 
 #[derive(Clone, Debug)]
-pub struct Indexed<I> {
+pub struct Indexed<I, C = i32> {
     iter: I,
-    x: i32,
-    y: i32,
-    width: i32,
+    x: C,
+    y: C,
+    width: C,
 }
 
-impl<I> Indexed<I> {
-    fn new(iter: I, width: i32) -> Indexed<I> { Indexed { iter, x: 0, y: 0, width } }
+impl<I, C: GridCoord> Indexed<I, C> {
+    fn new(iter: I, width: C) -> Indexed<I, C> {
+        Indexed { iter, x: C::default(), y: C::default(), width }
+    }
 }
 
-impl<I> Iterator for Indexed<I>
+impl<I, C> Iterator for Indexed<I, C>
 where
     I: Iterator,
+    C: GridCoord,
 {
-    type Item = (Location, <I as Iterator>::Item);
+    type Item = (Location<C>, <I as Iterator>::Item);
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let item = self.iter.next()?;
         let location = point2(self.x, self.y);
-        self.x += 1;
+        self.x = self.x + C::ONE;
         if self.x >= self.width {
-            self.x = 0;
-            self.y += 1;
+            self.x = C::default();
+            self.y = self.y + C::ONE;
         }
         Some((location, item))
     }
@@ -763,40 +636,48 @@ where
     fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
 }
 
-pub struct GridGraph<'a, T, FS, FH> {
-    grid: &'a Grid<T>,
-    goal: Location,
+pub struct GridGraph<'a, T, FS, FH, C = i32> {
+    grid: &'a Grid<T, C>,
+    goal: Location<C>,
     score_step: FS,
     heuristic_score: FH,
 }
 
-impl<'a, T, S, FS, FH> crate::search::SearchGraph for GridGraph<'a, T, FS, FH>
+impl<'a, T, S, FS, FH, C> crate::search::SearchGraph for GridGraph<'a, T, FS, FH, C>
 where
-    FS: Fn(Location, &T, &T) -> Option<S>,
+    FS: Fn(Location<C>, &T, &T) -> Option<S>,
     S: Copy + Default + Eq + Add<S, Output = S> + Ord,
-    FH: Fn(Vector<2, i32>) -> S,
+    FH: Fn(Vector<2, C::Delta>) -> S,
+    C: GridCoord,
 {
-    type Node = Location;
+    type Node = Location<C>;
     type Score = S;
 
-    fn neighbours(&self, current_loc: Location) -> impl Iterator<Item = (Location, S)> {
+    fn neighbours(&self, current_loc: Location<C>) -> impl Iterator<Item = (Location<C>, S)> {
         let current_val = self.grid.get(current_loc).unwrap();
-        self.grid.direct_neighbours(current_loc).flat_map(move |(dir, val)| {
-            let next_loc = current_loc + dir;
-            (self.score_step)(next_loc, current_val, val).map(|score| (next_loc, score))
+        Direction::ALL.into_iter().flat_map(move |dir| {
+            let next_loc = self.grid.neighbour(current_loc, dir)?;
+            let score = (self.score_step)(next_loc, current_val, &self.grid[next_loc])?;
+            Some((next_loc, score))
         })
     }
 
-    fn expected_state_size(&self) -> usize { (self.grid.width() * self.grid.height()) as usize }
+    fn expected_state_size(&self) -> usize { self.grid.len() }
 }
 
-impl<'a, T, S, FS, FH> crate::search::SearchGraphWithGoal for GridGraph<'a, T, FS, FH>
+impl<'a, T, S, FS, FH, C> crate::search::SearchGraphWithGoal for GridGraph<'a, T, FS, FH, C>
 where
-    FS: Fn(Location, &T, &T) -> Option<S>,
+    FS: Fn(Location<C>, &T, &T) -> Option<S>,
     S: Copy + Default + Eq + Add<S, Output = S> + Ord,
-    FH: Fn(Vector<2, i32>) -> S,
+    FH: Fn(Vector<2, C::Delta>) -> S,
+    C: GridCoord,
 {
-    fn is_goal(&self, curr: Location) -> bool { curr == self.goal }
+    fn is_goal(&self, curr: Location<C>) -> bool { curr == self.goal }
 
-    fn heuristic(&self, curr: Location) -> Self::Score { (self.heuristic_score)(self.goal - curr) }
+    /// The heuristic function is called with the signed vector from the current location to the
+    /// goal, which is `goal - curr`
+    fn heuristic(&self, curr: Location<C>) -> Self::Score {
+        let to_goal = vector2(self.goal.x().delta(curr.x()), self.goal.y().delta(curr.y()));
+        (self.heuristic_score)(to_goal)
+    }
 }
@@ -0,0 +1,244 @@
+use super::{Grid, Location};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use std::fs::File;
+use std::io::BufWriter;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnimationFormat {
+    Gif,
+    /// Animated PNG, which keeps the exact colors, but is supported by fewer viewers
+    Apng,
+}
+
+/// Collects frames of a grid simulation and writes them to a single animated image.
+///
+/// Each cell is drawn as a square of `scale` pixels using the `mapping` function, just like
+/// [Grid::render_to_image]. Frames that are recorded after `max_frames` has been reached are
+/// dropped, so a recorder can be left in a long-running loop.
+pub struct GridRecorder<F> {
+    mapping: F,
+    scale: u32,
+    delay_ms: u32,
+    max_frames: usize,
+    overlay_color: [u8; 4],
+    frames: Vec<RgbaImage>,
+}
+
+impl<F> GridRecorder<F> {
+    pub fn new<T>(mapping: F) -> Self
+    where
+        F: Fn(&T) -> [u8; 4],
+    {
+        GridRecorder {
+            mapping,
+            scale: 1,
+            delay_ms: 100,
+            max_frames: usize::MAX,
+            overlay_color: [255, 0, 0, 255],
+            frames: Vec::new(),
+        }
+    }
+
+    /// The size in pixels of each cell
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The time each frame is shown in milliseconds
+    pub fn delay(mut self, delay_ms: u32) -> Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    pub fn max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    /// The color that is used to draw the overlay locations
+    pub fn overlay_color(mut self, color: [u8; 4]) -> Self {
+        self.overlay_color = color;
+        self
+    }
+
+    pub fn frame_count(&self) -> usize { self.frames.len() }
+
+    pub fn is_full(&self) -> bool { self.frames.len() >= self.max_frames }
+
+    /// Adds the grid as the next frame. Returns false if the frame was dropped because the
+    /// maximum number of frames was already reached.
+    pub fn record<T>(&mut self, grid: &Grid<T>) -> bool
+    where
+        F: Fn(&T) -> [u8; 4],
+    {
+        self.record_with_overlay(grid, &[])
+    }
+
+    /// Adds the grid as the next frame, where the given locations (e.g. a path) are drawn on top
+    /// of the grid in the overlay color.
+    pub fn record_with_overlay<'a, T, I>(&mut self, grid: &Grid<T>, overlay: I) -> bool
+    where
+        F: Fn(&T) -> [u8; 4],
+        I: IntoIterator<Item = &'a Location>,
+    {
+        if self.is_full() {
+            return false;
+        }
+
+        let mut image = grid.to_image(self.scale, &self.mapping);
+        for location in overlay {
+            if !grid.is_valid_location(location) {
+                continue;
+            }
+            for dy in 0..self.scale {
+                for dx in 0..self.scale {
+                    let x = location.x() as u32 * self.scale + dx;
+                    let y = location.y() as u32 * self.scale + dy;
+                    *image.get_pixel_mut(x, y) = Rgba(self.overlay_color);
+                }
+            }
+        }
+
+        self.frames.push(image);
+        true
+    }
+
+    /// Records the initial grid and then the result of each call to `step`, until `step` returns
+    /// false or the maximum number of frames is reached.
+    pub fn record_steps<T, S>(&mut self, grid: &mut Grid<T>, mut step: S)
+    where
+        F: Fn(&T) -> [u8; 4],
+        S: FnMut(&mut Grid<T>) -> bool,
+    {
+        self.record(grid);
+        while !self.is_full() && step(grid) {
+            self.record(grid);
+        }
+    }
+
+    /// Writes all recorded frames to the given file. An APNG needs all frames to be the same size.
+    pub fn save(&self, filename: &str, format: AnimationFormat) {
+        let file = File::create(filename).expect("Expect creating the file to not be a problem");
+        let writer = BufWriter::new(file);
+        match format {
+            AnimationFormat::Gif => self.write_gif(writer),
+            AnimationFormat::Apng => self.write_apng(writer),
+        }
+        .expect("Expect saving to not be a problem");
+    }
+
+    fn write_gif(&self, writer: BufWriter<File>) -> Result<(), String> {
+        let mut encoder = GifEncoder::new(writer);
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+        let delay = Delay::from_numer_denom_ms(self.delay_ms, 1);
+        encoder
+            .encode_frames(
+                self.frames.iter().map(|image| Frame::from_parts(image.clone(), 0, 0, delay)),
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    fn write_apng(&self, writer: BufWriter<File>) -> Result<(), String> {
+        let Some(first) = self.frames.first() else {
+            return Err("No frames recorded".into());
+        };
+        // An APNG has a single size, so frames of grids with another size can't be written
+        assert!(
+            self.frames.iter().all(|frame| frame.dimensions() == first.dimensions()),
+            "All frames of an APNG need to be the same size as the first one"
+        );
+        let mut encoder = png::Encoder::new(writer, first.width(), first.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0).map_err(|e| e.to_string())?;
+        let delay = self.delay_ms.min(u16::MAX as u32) as u16;
+        encoder.set_frame_delay(delay, 1000).map_err(|e| e.to_string())?;
+
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        for frame in &self.frames {
+            writer.write_image_data(frame.as_raw()).map_err(|e| e.to_string())?;
+        }
+        writer.finish().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnimationFormat, GridRecorder};
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::PathBuf;
+
+    fn color(value: &u8) -> [u8; 4] { [*value * 50, 0, 0, 255] }
+
+    fn record_counter() -> GridRecorder<fn(&u8) -> [u8; 4]> {
+        let mut recorder = GridRecorder::new(color as fn(&u8) -> [u8; 4]).scale(3).max_frames(4);
+        let mut grid = Grid::new_default(0u8, 2, 2);
+        recorder.record_steps(&mut grid, |grid| {
+            grid[point2(0, 0)] += 1;
+            true
+        });
+        recorder
+    }
+
+    #[test]
+    fn max_frames_limits_recording() {
+        let mut recorder = record_counter();
+        assert_eq!(4, recorder.frame_count());
+        assert!(!recorder.record_with_overlay(&Grid::new_default(0u8, 2, 2), &[point2(0, 0)]));
+    }
+
+    #[test]
+    fn overlay_is_drawn_on_top() {
+        let mut recorder = GridRecorder::new(color).scale(2).overlay_color([0, 0, 255, 255]);
+        assert!(recorder.record_with_overlay(&Grid::new_default(1u8, 2, 2), &[point2(1, 0)]));
+        assert_eq!([50, 0, 0, 255], recorder.frames[0].get_pixel(1, 1).0);
+        assert_eq!([0, 0, 255, 255], recorder.frames[0].get_pixel(3, 1).0);
+    }
+
+    /// A file name that is unique for this test run, so tests running at the same time in other
+    /// processes don't overwrite each other's files
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("advent_lib_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn write_gif() {
+        let path = temp_path("grid_recorder.gif");
+        record_counter().save(path.to_str().unwrap(), AnimationFormat::Gif);
+
+        let decoder = GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(4, frames.len());
+        assert_eq!(6, frames[0].buffer().width());
+        assert_eq!(150, frames[3].buffer().get_pixel(2, 2)[0]);
+    }
+
+    #[test]
+    fn write_apng() {
+        let path = temp_path("grid_recorder.png");
+        record_counter().save(path.to_str().unwrap(), AnimationFormat::Apng);
+
+        let decoder = png::Decoder::new(BufReader::new(File::open(&path).unwrap()));
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(4, reader.info().animation_control().unwrap().num_frames);
+    }
+
+    #[test]
+    #[should_panic(expected = "same size")]
+    fn apng_frames_need_the_same_size() {
+        let mut recorder = GridRecorder::new(color);
+        recorder.record(&Grid::new_default(0u8, 2, 2));
+        recorder.record(&Grid::new_default(0u8, 3, 2));
+        recorder.save(
+            temp_path("uneven_frames.png").to_str().unwrap(),
+            AnimationFormat::Apng,
+        );
+    }
+}
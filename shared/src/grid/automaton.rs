@@ -0,0 +1,205 @@
+use super::{Grid, Location};
+use crate::direction::{CardinalDirection, Direction};
+use crate::geometry::point2;
+use fxhash::FxHashMap;
+use rayon::prelude::*;
+use std::hash::Hash;
+
+/// A view on the neighbourhood of a single cell, which is handed to the rule of an [Automaton].
+pub struct Neighbours<'a, T> {
+    grid: &'a Grid<T>,
+    location: Location,
+}
+
+impl<'a, T> Neighbours<'a, T> {
+    pub fn location(&self) -> Location { self.location }
+
+    /// The 4 direct neighbours that are within the grid, like [Grid::direct_neighbours]
+    pub fn direct(&self) -> impl Iterator<Item = (Direction, &'a T)> + use<'a, T> {
+        self.grid.direct_neighbours(self.location)
+    }
+
+    /// The 8 surrounding neighbours that are within the grid, including the diagonal ones
+    pub fn cardinal(&self) -> impl Iterator<Item = (CardinalDirection, &'a T)> + use<'a, T> {
+        let (grid, location) = (self.grid, self.location);
+        CardinalDirection::ALL
+            .into_iter()
+            .flat_map(move |dir| grid.get(location + dir).map(|value| (dir, value)))
+    }
+
+    /// Returns all 8 surrounding neighbours, or `None` at the edge of the grid, like
+    /// [Grid::cardinal_neighbours]
+    pub fn cardinal_array(&self) -> Option<[&'a T; 8]>
+    where
+        T: Default + Copy,
+    {
+        self.grid.cardinal_neighbours(self.location)
+    }
+
+    pub fn count_direct(&self, predicate: impl Fn(&T) -> bool) -> usize {
+        self.direct().filter(|(_, value)| predicate(value)).count()
+    }
+
+    pub fn count_cardinal(&self, predicate: impl Fn(&T) -> bool) -> usize {
+        self.cardinal().filter(|(_, value)| predicate(value)).count()
+    }
+}
+
+/// Runs a cellular automaton on a [Grid]. Each step every cell is replaced by the result of the
+/// rule, which only sees the state of the previous step. Two buffers are kept, so no grid is
+/// allocated while stepping.
+pub struct Automaton<T, F> {
+    current: Grid<T>,
+    next: Grid<T>,
+    rule: F,
+    parallel: bool,
+    steps: usize,
+}
+
+impl<T, F> Automaton<T, F>
+where
+    T: Clone + PartialEq + Send + Sync,
+    F: Fn(&T, Neighbours<'_, T>) -> T + Sync,
+{
+    pub fn new(grid: Grid<T>, rule: F) -> Self {
+        Automaton { next: grid.clone(), current: grid, rule, parallel: false, steps: 0 }
+    }
+
+    /// Calculates the rows of each step in parallel, which pays off for large grids
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    pub fn grid(&self) -> &Grid<T> { &self.current }
+
+    pub fn into_grid(self) -> Grid<T> { self.current }
+
+    /// The number of steps that have been executed so far. Steps that are skipped because of a
+    /// detected cycle in [Automaton::run_steps] are not counted.
+    pub fn steps(&self) -> usize { self.steps }
+
+    /// Executes a single step and returns the number of cells that changed
+    pub fn step(&mut self) -> usize {
+        let width = self.current.width() as usize;
+        let current = &self.current;
+        let rule = &self.rule;
+        let calculate_row = |(y, row): (usize, &mut [T])| {
+            let mut changed = 0;
+            for (x, cell) in row.iter_mut().enumerate() {
+                let location = point2(x as i32, y as i32);
+                let old_value = &current[location];
+                let new_value = rule(old_value, Neighbours { grid: current, location });
+                if new_value != *old_value {
+                    changed += 1;
+                }
+                *cell = new_value;
+            }
+            changed
+        };
+
+        let changed = if width == 0 {
+            0
+        } else if self.parallel {
+            self.next.items.par_chunks_mut(width).enumerate().map(calculate_row).sum()
+        } else {
+            self.next.items.chunks_mut(width).enumerate().map(calculate_row).sum()
+        };
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.steps += 1;
+        changed
+    }
+
+    /// Keeps stepping until a step doesn't change any cell. Returns the total number of steps
+    /// executed so far, including the last one that didn't change anything.
+    pub fn run_until_stable(&mut self) -> usize {
+        while self.step() > 0 {}
+        self.steps
+    }
+
+    /// Executes `count` more steps. When a state repeats itself, the remaining steps are skipped
+    /// by jumping ahead a whole number of cycles, which allows for very large step counts.
+    pub fn run_steps(&mut self, count: usize) -> &Grid<T>
+    where
+        T: Eq + Hash,
+    {
+        let mut seen = FxHashMap::<Grid<T>, usize>::default();
+        let mut remaining = count;
+        while remaining > 0 {
+            if let Some(previous) = seen.insert(self.current.clone(), remaining) {
+                remaining %= previous - remaining;
+                break;
+            }
+            self.step();
+            remaining -= 1;
+        }
+
+        for _ in 0..remaining {
+            self.step();
+        }
+        &self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Automaton, Neighbours};
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    fn game_of_life(
+        grid: Grid<char>,
+    ) -> Automaton<char, impl Fn(&char, Neighbours<char>) -> char + Sync> {
+        Automaton::new(grid, |&cell, neighbours| {
+            match (cell, neighbours.count_cardinal(|&c| c == '#')) {
+                ('#', 2 | 3) | ('.', 3) => '#',
+                _ => '.',
+            }
+        })
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let mut automaton = game_of_life(parse(".....\n..#..\n..#..\n..#..\n....."));
+        assert_eq!(4, automaton.step());
+        assert_eq!(
+            parse(".....\n.....\n.###.\n.....\n....."),
+            *automaton.grid()
+        );
+        assert_eq!(
+            parse(".....\n.....\n.###.\n.....\n....."),
+            *automaton.run_steps(1_000_000)
+        );
+        assert_eq!(
+            parse(".....\n..#..\n..#..\n..#..\n....."),
+            *automaton.run_steps(999)
+        );
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let grid = parse("#..#.\n.##..\n#...#\n.#.#.\n##..#");
+        let mut sequential = game_of_life(grid.clone());
+        let mut parallel = game_of_life(grid).parallel(true);
+        for _ in 0..5 {
+            assert_eq!(sequential.step(), parallel.step());
+            assert_eq!(sequential.grid(), parallel.grid());
+        }
+    }
+
+    #[test]
+    fn run_until_stable_stops_when_nothing_changes() {
+        let mut automaton = Automaton::new(parse("#...."), |&cell, neighbours| {
+            if neighbours.direct().any(|(_, &c)| c == '#') { '#' } else { cell }
+        });
+        assert_eq!(5, automaton.run_until_stable());
+        assert_eq!(parse("#####"), automaton.into_grid());
+    }
+}
@@ -0,0 +1,315 @@
+use super::{Grid, Location, Size};
+use crate::direction::{CardinalDirection, Direction};
+use crate::geometry::{Vector, point2, vector2};
+use std::ops::{Index, IndexMut};
+
+/// A grid that is stored with a border of one sentinel cell around it. Every cell on the grid has
+/// all 8 neighbours in storage, so stepping from a cell is a fixed offset to its index without
+/// any bounds checks. The locations are the same as those of the original grid, so the border
+/// is at x or y of -1 and at the width or height.
+///
+/// The indices are only meant to be used in hot loops, using [BorderedGrid::step] to move around.
+/// Stepping from a border cell is not supported, and the border always keeps the sentinel.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BorderedGrid<T> {
+    padded: Grid<T>,
+    sentinel: T,
+}
+
+impl<T> Grid<T> {
+    /// Converts this grid into a [BorderedGrid] with the sentinel value on the border
+    pub fn with_border(mut self, sentinel: T) -> BorderedGrid<T>
+    where
+        T: Clone,
+    {
+        self.grow(vector2(1, 1), vector2(1, 1), sentinel.clone());
+        BorderedGrid { padded: self, sentinel }
+    }
+}
+
+impl<T> BorderedGrid<T> {
+    pub fn new_default(value: T, sentinel: T, width: i32, height: i32) -> BorderedGrid<T>
+    where
+        T: Clone,
+    {
+        Grid::new_default(value, width, height).with_border(sentinel)
+    }
+
+    pub fn width(&self) -> i32 { self.padded.width() - 2 }
+
+    pub fn height(&self) -> i32 { self.padded.height() - 2 }
+
+    pub fn size(&self) -> Size { vector2(self.width(), self.height()) }
+
+    pub fn is_valid_location(&self, location: &Location) -> bool {
+        (0..self.width()).contains(&location.x()) && (0..self.height()).contains(&location.y())
+    }
+
+    /// The index of the location, which may also be on the border
+    pub fn index_of(&self, location: Location) -> usize {
+        assert!(
+            (-1..=self.width()).contains(&location.x())
+                && (-1..=self.height()).contains(&location.y()),
+            "Location {location:?} is not on the grid or its border"
+        );
+        ((location.x() + 1) + (location.y() + 1) * self.padded.width()) as usize
+    }
+
+    /// The index of a location on the grid, which excludes the border, so all its neighbours are
+    /// in storage
+    fn grid_index_of(&self, location: Location) -> usize {
+        assert!(
+            self.is_valid_location(&location),
+            "Location {location:?} is not on the grid"
+        );
+        ((location.x() + 1) + (location.y() + 1) * self.padded.width()) as usize
+    }
+
+    pub fn location_of(&self, index: usize) -> Location {
+        let stride = self.padded.width() as usize;
+        point2((index % stride) as i32 - 1, (index / stride) as i32 - 1)
+    }
+
+    /// How much the index changes when taking a step in the direction, which can be a
+    /// [Direction] or a [CardinalDirection]
+    pub fn offset(&self, direction: impl Into<Vector<2, i32>>) -> isize {
+        let step = direction.into();
+        (step.x() + step.y() * self.padded.width()) as isize
+    }
+
+    /// The index of the neighbour in the direction of the cell at the index. The index needs to
+    /// be of a cell on the grid, not on the border.
+    #[inline]
+    pub fn step(&self, index: usize, direction: impl Into<Vector<2, i32>>) -> usize {
+        index.wrapping_add_signed(self.offset(direction))
+    }
+
+    #[inline]
+    pub fn at(&self, index: usize) -> &T { &self.padded.items[index] }
+
+    /// Panics when the index is on the border, because that has to keep the sentinel
+    #[inline]
+    pub fn at_mut(&mut self, index: usize) -> &mut T {
+        assert!(!self.is_border(index), "Index {index} is on the border");
+        &mut self.padded.items[index]
+    }
+
+    /// # Safety
+    ///
+    /// This method does not do any boundary checks, so the index needs to be of a cell on the
+    /// grid or its border, like a [BorderedGrid::step] from a cell on the grid.
+    #[inline]
+    pub unsafe fn at_unchecked(&self, index: usize) -> &T {
+        unsafe { self.padded.items.get_unchecked(index) }
+    }
+
+    /// # Safety
+    ///
+    /// This method does not do any boundary checks, so the index needs to be of a cell on the
+    /// grid or its border, like a [BorderedGrid::step] from a cell on the grid. Only the cells on
+    /// the grid may be changed, the border has to keep the sentinel.
+    #[inline]
+    pub unsafe fn at_unchecked_mut(&mut self, index: usize) -> &mut T {
+        unsafe { self.padded.items.get_unchecked_mut(index) }
+    }
+
+    pub fn is_border(&self, index: usize) -> bool {
+        !self.is_valid_location(&self.location_of(index))
+    }
+
+    /// The indices of all cells on the grid in row order, skipping the border
+    pub fn indices(&self) -> impl Iterator<Item = usize> + use<T> {
+        let stride = self.padded.width() as usize;
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        (1..=height).flat_map(move |y| (1..=width).map(move |x| x + y * stride))
+    }
+
+    /// Returns the value on the grid, or `None` on the border and outside the grid, like
+    /// [Grid::get]
+    pub fn get(&self, location: Location) -> Option<&T> {
+        self.is_valid_location(&location).then(|| self.at(self.index_of(location)))
+    }
+
+    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> {
+        if self.is_valid_location(&location) {
+            let index = self.grid_index_of(location);
+            // Safety: the location is on the grid
+            Some(unsafe { self.at_unchecked_mut(index) })
+        } else {
+            None
+        }
+    }
+
+    /// The 4 direct neighbours, where the sentinel is returned for the neighbours on the border
+    pub fn direct_neighbours(&self, location: Location) -> [(Direction, &T); 4] {
+        let index = self.grid_index_of(location);
+        // Safety: every neighbour of a cell on the grid is on the grid or the border
+        Direction::ALL.map(|dir| (dir, unsafe { self.at_unchecked(self.step(index, dir)) }))
+    }
+
+    /// The 8 surrounding neighbours in the order of [CardinalDirection::ALL], where the sentinel
+    /// is returned for the neighbours on the border
+    pub fn cardinal_neighbours(&self, location: Location) -> [&T; 8] {
+        let index = self.grid_index_of(location);
+        // Safety: every neighbour of a cell on the grid is on the grid or the border
+        CardinalDirection::ALL.map(|dir| unsafe { self.at_unchecked(self.step(index, dir)) })
+    }
+
+    /// Flood fills the area of equal cells around the start with the value, like [Grid::fill].
+    /// The border is never filled, also when the replaced cells are equal to the sentinel.
+    pub fn fill(&mut self, start: Location, value: T) -> usize
+    where
+        T: PartialEq + Clone,
+    {
+        let Some(accept_cell) = self.get(start).cloned() else {
+            return 0; // Not in range, just return
+        };
+        if accept_cell == value {
+            return 0;
+        }
+
+        let start = self.grid_index_of(start);
+        if accept_cell == self.sentinel {
+            self.fill_from(start, accept_cell, value, |grid, index| {
+                !grid.is_border(index)
+            })
+        } else {
+            // The border can't match, so the fill never leaves the grid
+            self.fill_from(start, accept_cell, value, |_, _| true)
+        }
+    }
+
+    fn fill_from<F>(&mut self, start: usize, accept_cell: T, value: T, is_on_grid: F) -> usize
+    where
+        T: PartialEq + Clone,
+        F: Fn(&Self, usize) -> bool,
+    {
+        // Safety: the start is on the grid, and only cells on the grid are pushed on the stack,
+        // so every step from them is on the grid or the border
+        *unsafe { self.at_unchecked_mut(start) } = value.clone();
+        let mut stack = vec![start];
+        let mut count_cells = 1;
+        while let Some(index) = stack.pop() {
+            for dir in Direction::ALL {
+                let next = self.step(index, dir);
+                // The border check is only needed when the cell matches, so it is done last
+                if *unsafe { self.at_unchecked(next) } == accept_cell && is_on_grid(self, next) {
+                    *unsafe { self.at_unchecked_mut(next) } = value.clone();
+                    count_cells += 1;
+                    stack.push(next);
+                }
+            }
+        }
+        count_cells
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        let stride = self.padded.width() as usize;
+        let (width, height) = (self.width(), self.height());
+        let items = self
+            .padded
+            .items
+            .into_iter()
+            .enumerate()
+            .filter(|(ix, _)| (1..=width as usize).contains(&(ix % stride)))
+            .filter(|(ix, _)| (1..=height as usize).contains(&(ix / stride)))
+            .map(|(_, value)| value)
+            .collect();
+        Grid { items, size: vector2(width, height) }
+    }
+}
+
+impl<T> Index<Location> for BorderedGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: Location) -> &Self::Output { self.get(index).unwrap() }
+}
+
+impl<T> IndexMut<Location> for BorderedGrid<T> {
+    fn index_mut(&mut self, index: Location) -> &mut Self::Output { self.get_mut(index).unwrap() }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::{CardinalDirection, Direction};
+    use crate::geometry::point2;
+    use crate::grid::{BorderedGrid, Grid};
+
+    #[test]
+    fn neighbours_include_sentinel() {
+        let grid: Grid<char> = Grid::from_ascii("ab\ncd");
+        let bordered = grid.clone().with_border('#');
+        assert_eq!(grid.size(), bordered.size());
+        assert_eq!('d', bordered[point2(1, 1)]);
+        assert_eq!(None, bordered.get(point2(-1, 0)));
+        assert_eq!(
+            [
+                (Direction::North, &'#'),
+                (Direction::East, &'b'),
+                (Direction::South, &'c'),
+                (Direction::West, &'#')
+            ],
+            bordered.direct_neighbours(point2(0, 0))
+        );
+        assert_eq!(
+            [&'b', &'#', &'#', &'#', &'#', &'#', &'c', &'a'],
+            bordered.cardinal_neighbours(point2(1, 1))
+        );
+        assert_eq!(grid, bordered.into_grid());
+    }
+
+    #[test]
+    fn index_arithmetic() {
+        let grid: Grid<char> = Grid::from_ascii("abc\ndef");
+        let bordered = grid.with_border('#');
+        let indices = bordered.indices().collect::<Vec<_>>();
+        assert_eq!(6, indices.len());
+        assert_eq!(
+            "abcdef",
+            indices.iter().map(|&ix| *bordered.at(ix)).collect::<String>()
+        );
+
+        let start = bordered.index_of(point2(1, 0));
+        assert_eq!(point2(1, 0), bordered.location_of(start));
+        assert_eq!(
+            'f',
+            *bordered.at(bordered.step(start, CardinalDirection::SE))
+        );
+        let above = bordered.step(start, CardinalDirection::N);
+        assert!(bordered.is_border(above));
+        assert_eq!(point2(1, -1), bordered.location_of(above));
+    }
+
+    #[test]
+    #[should_panic]
+    fn border_cannot_be_changed() {
+        let mut bordered = Grid::new_default('.', 2, 2).with_border('#');
+        let above = bordered.step(bordered.index_of(point2(0, 0)), Direction::North);
+        *bordered.at_mut(above) = '.';
+    }
+
+    #[test]
+    fn fill_matches_grid() {
+        let input = "..#..\n.##..\n#....\n..#.#";
+        let mut grid: Grid<char> = Grid::from_ascii(input);
+        let mut bordered = grid.clone().with_border('X');
+        assert_eq!(
+            grid.fill(point2(4, 0), 'o'),
+            bordered.fill(point2(4, 0), 'o')
+        );
+        assert_eq!(
+            grid.fill(point2(0, 0), 'o'),
+            bordered.fill(point2(0, 0), 'o')
+        );
+        assert_eq!(0, bordered.fill(point2(5, 0), 'o'));
+        assert_eq!(grid, bordered.into_grid());
+    }
+
+    #[test]
+    fn fill_cells_equal_to_sentinel() {
+        let mut bordered = BorderedGrid::new_default('.', '.', 3, 3);
+        assert_eq!(9, bordered.fill(point2(1, 1), 'o'));
+        assert_eq!('.', *bordered.at(bordered.index_of(point2(-1, 1))));
+        assert_eq!(Grid::new_default('o', 3, 3), bordered.into_grid());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::direction::Direction;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, Mul, Range, Sub};
+
+/// An integer type that can be used for the coordinates of a [super::Grid]. This makes it
+/// possible to use the same type for the grid as for the rest of the puzzle (e.g. `i64` or
+/// `usize`) without casting every location.
+pub trait GridCoord:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Send
+    + Sync
+    + 'static
+{
+    const ONE: Self;
+
+    /// The signed type that holds the difference between two coordinates
+    type Delta: Copy + Ord + Debug + Default + Add<Output = Self::Delta> + Sub<Output = Self::Delta>;
+
+    /// Converts a coordinate that is known to be on the grid to an index
+    fn to_index(self) -> usize;
+
+    fn from_index(index: usize) -> Self;
+
+    /// Moves the coordinate by -1, 0 or 1, or returns `None` when that can't be represented
+    fn checked_step(self, delta: i8) -> Option<Self>;
+
+    /// The signed difference `self - other`
+    fn delta(self, other: Self) -> Self::Delta;
+
+    /// Moves the coordinate by the signed difference, or returns `None` when that can't be
+    /// represented
+    fn checked_offset(self, delta: Self::Delta) -> Option<Self>;
+
+    /// Wraps the coordinate into the range of `0..size`, also for negative coordinates
+    fn wrap(self, size: Self) -> Self;
+}
+
+macro_rules! grid_coord {
+    ($($type:ty => $delta:ty),*) => {
+        $(
+            impl GridCoord for $type {
+                const ONE: Self = 1;
+
+                type Delta = $delta;
+
+                #[inline]
+                fn to_index(self) -> usize { self as usize }
+
+                #[inline]
+                fn from_index(index: usize) -> Self { index as $type }
+
+                #[inline]
+                fn checked_step(self, delta: i8) -> Option<Self> {
+                    match delta {
+                        0 => Some(self),
+                        delta if delta > 0 => self.checked_add(1),
+                        _ => self.checked_sub(1),
+                    }
+                }
+
+                #[inline]
+                fn delta(self, other: Self) -> $delta { self as $delta - other as $delta }
+
+                #[inline]
+                fn checked_offset(self, delta: $delta) -> Option<Self> {
+                    (self as $delta).checked_add(delta)?.try_into().ok()
+                }
+
+                #[inline]
+                fn wrap(self, size: Self) -> Self { self.rem_euclid(size) }
+            }
+        )*
+    };
+}
+
+grid_coord!(i32 => i32, i64 => i64, usize => isize);
+
+/// Iterates over all coordinates in the range, since [std::iter::Step] can't be used for a generic
+/// coordinate
+pub(super) fn coords<C: GridCoord>(range: Range<C>) -> impl Iterator<Item = C> {
+    std::iter::successors(Some(range.start), |&c| Some(c + C::ONE))
+        .take_while(move |&c| c < range.end)
+}
+
+/// The change of x and y when taking a step in the direction
+pub(super) const fn direction_steps(direction: Direction) -> (i8, i8) {
+    match direction {
+        Direction::North => (0, -1),
+        Direction::East => (1, 0),
+        Direction::South => (0, 1),
+        Direction::West => (-1, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::Direction;
+    use crate::geometry::{point2, vector2};
+    use crate::grid::Grid;
+    use crate::search::{SearchGraphWithGoal, a_star_search_from};
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    #[test]
+    fn wide_coordinates() {
+        let result: Result<Grid<char, i64>, Error<_>> = Grid::parse_complete("#..\n.#.");
+        let mut grid = result.unwrap();
+        assert_eq!(vector2(3i64, 2), grid.size());
+        assert_eq!('#', grid[point2(1i64, 1)]);
+        assert_eq!(None, grid.get(point2(3_000_000_000i64, 0)));
+
+        grid[point2(2i64, 0)] = '#';
+        let walls = grid.locations_where(|&c| c == '#').collect::<Vec<_>>();
+        assert_eq!(vec![point2(0, 0), point2(2, 0), point2(1i64, 1)], walls);
+        assert_eq!(
+            "Grid(3x2)\n┌───┐\n│#.#│\n│.#.│\n└───┘\n",
+            format!("{grid:?}")
+        );
+    }
+
+    #[test]
+    fn unsigned_neighbours() {
+        let grid = Grid::<u8, usize>::new_default(b'.', 3, 3);
+        assert_eq!(None, grid.neighbour(point2(0, 0), Direction::West));
+        assert_eq!(
+            Some(point2(1, 0)),
+            grid.neighbour(point2(0, 0), Direction::East)
+        );
+        assert_eq!(None, grid.neighbour(point2(2, 2), Direction::South));
+        assert_eq!(2, grid.direct_neighbours(point2(0, 0)).count());
+        assert_eq!(4, grid.direct_neighbours(point2(1, 1)).count());
+    }
+
+    #[test]
+    fn lines_and_fill_with_unsigned_coordinates() {
+        let result: Result<Grid<char, usize>, Error<_>> = Grid::parse_complete("ab\ncd");
+        let mut grid = result.unwrap();
+        let north = grid.north_lines().flatten().map(|(_, &c)| c).collect::<String>();
+        assert_eq!("cadb", north);
+        let west = grid.west_lines().flatten().map(|(_, &c)| c).collect::<String>();
+        assert_eq!("badc", west);
+        assert_eq!(&'d', grid.get_infinite(point2(3, 5)));
+        assert_eq!(Grid::from_ascii("d"), grid.sub_grid(1..2, 1..2));
+
+        grid[point2(1, 0)] = 'a';
+        assert_eq!(2, grid.fill(point2(1, 0), 'x'));
+        assert_eq!(Grid::from_ascii("xx\ncd"), grid);
+        assert!(grid.draw_with_overlay(&[point2(0, 1)], '#').contains("│#d│"));
+    }
+
+    #[test]
+    fn mut_line_towards_zero_with_unsigned_coordinates() {
+        let mut grid = Grid::<u8, usize>::new_default(0, 4, 3);
+        grid.mut_line(point2(3, 2), vector2(-1, -1), |value| *value += 1);
+        grid.mut_line(point2(0, 1), vector2(1, 0), |value| *value += 2);
+        let marked = grid.locations_where(|&value| value > 0).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                point2(1, 0),
+                point2(0, 1),
+                point2(1, 1),
+                point2(2, 1),
+                point2(3, 1),
+                point2(3, 2)
+            ],
+            marked
+        );
+        assert_eq!(3, grid[point2(2, 1)]);
+    }
+
+    #[test]
+    fn heuristic_gets_signed_vector_to_goal() {
+        let grid = Grid::<char, usize>::new_default('.', 4, 4);
+        let graph = grid.search_graph(point2(1, 3), |_, _, _| Some(1isize), |v| v.x() * 10 + v.y());
+        assert_eq!(-18, graph.heuristic(point2(3, 1)));
+
+        let grid = Grid::<char>::new_default('.', 4, 4);
+        let graph = grid.search_graph(point2(1, 3), |_, _, _| Some(1), |v| v.x() * 10 + v.y());
+        assert_eq!(12, graph.heuristic(point2(0, 1)));
+    }
+
+    #[test]
+    fn search_with_unsigned_coordinates() {
+        let result: Result<Grid<char, usize>, Error<_>> = Grid::parse_complete(".#.\n.#.\n...");
+        let grid = result.unwrap();
+        let graph = grid.search_graph(
+            point2(2, 0),
+            |_, _, &next| (next != '#').then_some(1usize),
+            |distance| distance.x().unsigned_abs() + distance.y().unsigned_abs(),
+        );
+        let (score, path) = a_star_search_from(&graph, [point2(0, 0)]).unwrap();
+        assert_eq!(6, score);
+        assert_eq!(7, path.len());
+    }
+}
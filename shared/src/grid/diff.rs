@@ -0,0 +1,148 @@
+use super::{Grid, Location};
+use crate::geometry::point2;
+use std::fmt::Write;
+
+const EXPECTED_COLOR: &str = "\x1b[1;32m";
+const ACTUAL_COLOR: &str = "\x1b[1;31m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// A cell that is different between two grids. The value is `None` when the location is not on
+/// that grid, which happens when the grids have different sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange<'a, T> {
+    pub location: Location,
+    pub old: Option<&'a T>,
+    pub new: Option<&'a T>,
+}
+
+impl<T> Grid<T> {
+    /// All cells that changed from this grid to the other, in row order. When the sizes are
+    /// different, the cells that only exist on one of the grids are included as well.
+    pub fn diff<'a>(&'a self, other: &'a Grid<T>) -> Vec<CellChange<'a, T>>
+    where
+        T: PartialEq,
+    {
+        let width = self.width().max(other.width());
+        let height = self.height().max(other.height());
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| point2(x, y)))
+            .map(|location| CellChange {
+                location,
+                old: self.get(location),
+                new: other.get(location),
+            })
+            .filter(|change| change.old != change.new)
+            .collect()
+    }
+
+    /// Renders this grid as the expected value next to the actual grid, where the cells that are
+    /// different are highlighted with ANSI colors. Returns `None` when the grids are equal.
+    pub fn diff_report(&self, actual: &Grid<T>) -> Option<String>
+    where
+        T: PartialEq + Copy + Into<char>,
+    {
+        let changes = self.diff(actual);
+        if changes.is_empty() {
+            return None;
+        }
+
+        let mut result = String::new();
+        writeln!(
+            result,
+            "{} cells differ, expected {}x{} (left) but was {}x{} (right)",
+            changes.len(),
+            self.width(),
+            self.height(),
+            actual.width(),
+            actual.height()
+        )
+        .unwrap();
+        for y in 0..self.height().max(actual.height()) {
+            render_row(&mut result, self, actual, y, EXPECTED_COLOR);
+            result.push_str("   ");
+            render_row(&mut result, actual, self, y, ACTUAL_COLOR);
+            result.push('\n');
+        }
+        Some(result)
+    }
+}
+
+/// Writes a single row of the grid between borders, padding rows that are not on the grid
+fn render_row<T>(result: &mut String, grid: &Grid<T>, other: &Grid<T>, y: i32, color: &str)
+where
+    T: PartialEq + Copy + Into<char>,
+{
+    if !grid.y_range().contains(&y) {
+        result.extend(std::iter::repeat_n(' ', grid.width() as usize + 2));
+        return;
+    }
+
+    result.push('│');
+    for x in grid.x_range() {
+        let location = point2(x, y);
+        let value = grid[location];
+        if other.get(location) == Some(&value) {
+            result.push(value.into());
+        } else {
+            write!(result, "{color}{}{RESET_COLOR}", value.into()).unwrap();
+        }
+    }
+    result.push('│');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CellChange;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+
+    #[test]
+    fn changed_cells() {
+        let before: Grid<char> = Grid::from_ascii("#..\n.#.");
+        let after: Grid<char> = Grid::from_ascii("#.#\n...");
+        assert_eq!(
+            vec![
+                CellChange { location: point2(2, 0), old: Some(&'.'), new: Some(&'#') },
+                CellChange { location: point2(1, 1), old: Some(&'#'), new: Some(&'.') },
+            ],
+            before.diff(&after)
+        );
+        assert!(before.diff(&before).is_empty());
+        assert_eq!(None, before.diff_report(&before));
+    }
+
+    #[test]
+    fn different_sizes() {
+        let small: Grid<char> = Grid::from_ascii("#.");
+        let large: Grid<char> = Grid::from_ascii("#.#\n...");
+        let changes = small.diff(&large);
+        assert_eq!(4, changes.len());
+        assert_eq!(
+            CellChange { location: point2(2, 0), old: None, new: Some(&'#') },
+            changes[0]
+        );
+    }
+
+    #[test]
+    fn highlighted_report() {
+        let expected: Grid<char> = Grid::from_ascii("#.\n..");
+        let actual: Grid<char> = Grid::from_ascii("#.\n.#");
+        assert_eq!(
+            Some(
+                "1 cells differ, expected 2x2 (left) but was 2x2 (right)\n\
+                 │#.│   │#.│\n\
+                 │.\x1b[1;32m.\x1b[0m│   │.\x1b[1;31m#\x1b[0m│\n"
+                    .to_string()
+            ),
+            expected.diff_report(&actual)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "1 cells differ")]
+    fn assert_equal_grids() {
+        let expected: Grid<char> = Grid::from_ascii("#.\n..");
+        crate::assert_grid_eq!(expected, expected.clone());
+        crate::assert_grid_eq!(expected, Grid::from_ascii("##\n.."));
+    }
+}
@@ -0,0 +1,192 @@
+use super::{Grid, Location, LocationSet};
+use crate::direction::Direction;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Iterates over the locations that are reached in each step of a breadth first search through a
+/// grid. The first item contains the sources, each next item all locations that are one step
+/// further away and have not been reached before.
+pub struct Frontiers<'a, T, F> {
+    grid: &'a Grid<T>,
+    passable: F,
+    visited: LocationSet,
+    frontier: Vec<Location>,
+}
+
+impl<'a, T, F> Iterator for Frontiers<'a, T, F>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    type Item = Vec<Location>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frontier.is_empty() {
+            return None;
+        }
+
+        let mut next_frontier = Vec::with_capacity(self.frontier.len() * 2);
+        for &location in &self.frontier {
+            let current = &self.grid[location];
+            for dir in Direction::ALL {
+                let next = location + dir;
+                if let Some(value) = self.grid.get(next)
+                    && !self.visited.contains(&next)
+                    && (self.passable)(current, value)
+                {
+                    self.visited.insert(next);
+                    next_frontier.push(next);
+                }
+            }
+        }
+
+        Some(std::mem::replace(&mut self.frontier, next_frontier))
+    }
+}
+
+impl<T> Grid<T> {
+    /// Returns an iterator with the set of locations reached in each step of a breadth first
+    /// search from all sources at once. The `passable` function determines if a step can be made
+    /// from the first cell to the second.
+    pub fn frontiers<I, F>(&self, sources: I, passable: F) -> Frontiers<'_, T, F>
+    where
+        I: IntoIterator<Item = Location>,
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut visited = LocationSet::for_grid(self);
+        let mut frontier = Vec::new();
+        for source in sources {
+            if self.is_valid_location(&source) && visited.insert(source) {
+                frontier.push(source);
+            }
+        }
+
+        Frontiers { grid: self, passable, visited, frontier }
+    }
+
+    /// Calculates the number of steps needed to reach each cell from the closest source. Cells
+    /// that can't be reached are `None`. The `passable` function determines if a step can be made
+    /// from the first cell to the second.
+    pub fn distance_map<I, F>(&self, sources: I, passable: F) -> Grid<Option<u32>>
+    where
+        I: IntoIterator<Item = Location>,
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut distances = self.map(|_| None);
+        for (steps, frontier) in self.frontiers(sources, passable).enumerate() {
+            for location in frontier {
+                distances[location] = Some(steps as u32);
+            }
+        }
+        distances
+    }
+
+    /// Calculates the lowest cost to reach each cell from the closest source. The `step_cost`
+    /// function returns the cost of stepping from the first cell to the second, or `None` when
+    /// that step is not possible.
+    pub fn weighted_distance_map<I, F>(&self, sources: I, step_cost: F) -> Grid<Option<u32>>
+    where
+        I: IntoIterator<Item = Location>,
+        F: Fn(&T, &T) -> Option<u32>,
+    {
+        let mut distances = self.map(|_| None);
+        let mut queue = BinaryHeap::new();
+        for source in sources {
+            if let Some(ix) = self.index_from_location(source) {
+                distances.items[ix] = Some(0);
+                queue.push(Reverse((0, ix)));
+            }
+        }
+
+        while let Some(Reverse((cost, ix))) = queue.pop() {
+            if distances.items[ix].is_some_and(|known| known < cost) {
+                continue; // Already found a cheaper route
+            }
+
+            let location = Location::from((self.size, ix));
+            let current = &self.items[ix];
+            for dir in Direction::ALL {
+                if let Some(next_ix) = self.index_from_location(location + dir)
+                    && let Some(step) = step_cost(current, &self.items[next_ix])
+                {
+                    let next_cost = cost + step;
+                    if distances.items[next_ix].is_none_or(|known| next_cost < known) {
+                        distances.items[next_ix] = Some(next_cost);
+                        queue.push(Reverse((next_cost, next_ix)));
+                    }
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+impl Grid<Option<u32>> {
+    /// All locations that can be reached in at most the given number of steps
+    pub fn locations_within(&self, steps: u32) -> impl Iterator<Item = Location> + '_ {
+        self.entries()
+            .filter(move |(_, distance)| distance.is_some_and(|d| d <= steps))
+            .map(|(location, _)| location)
+    }
+
+    /// All locations where a walk of exactly the given number of steps can end, when walking back
+    /// and forth is allowed. Those are the locations within reach that have the same parity.
+    pub fn locations_reachable_in(&self, steps: u32) -> impl Iterator<Item = Location> + '_ {
+        self.entries()
+            .filter(move |(_, distance)| distance.is_some_and(|d| d <= steps && d % 2 == steps % 2))
+            .map(|(location, _)| location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn garden() -> Grid<char> {
+        let input = "...........\n\
+                     .....###.#.\n\
+                     .###.##..#.\n\
+                     ..#.#...#..\n\
+                     ....#.#....\n\
+                     .##..S####.\n\
+                     .##..#...#.\n\
+                     .......##..\n\
+                     .##.#.####.\n\
+                     .##..##.##.\n\
+                     ...........";
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn garden_steps() {
+        let garden = garden();
+        let start = garden.find(|&c| c == 'S').unwrap();
+        let distances = garden.distance_map([start], |_, &to| to != '#');
+        assert_eq!(Some(0), distances[start]);
+        assert_eq!(None, distances[point2(4, 4)]);
+        assert_eq!(16, distances.locations_reachable_in(6).count());
+        assert_eq!(29, distances.locations_within(6).count());
+    }
+
+    #[test]
+    fn frontiers_per_step() {
+        let garden = garden();
+        let start = garden.find(|&c| c == 'S').unwrap();
+        let sizes = garden.frontiers([start], |_, &to| to != '#').take(4).map(|f| f.len());
+        assert_eq!(vec![1, 2, 3, 4], sizes.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn weighted_distances() {
+        let grid: Result<Grid<char>, Error<_>> = Grid::parse_complete("119\n191\n111");
+        let grid = grid.unwrap().map(|&c| c as u32 - '0' as u32);
+        let distances = grid.weighted_distance_map([point2(0, 0)], |_, &to| Some(to));
+        assert_eq!(Some(4), distances[point2(2, 2)]);
+        assert_eq!(Some(5), distances[point2(2, 1)]);
+        assert_eq!(Some(10), distances[point2(2, 0)]);
+    }
+}
@@ -0,0 +1,217 @@
+use super::{Grid, Location};
+use std::hash::Hash;
+use std::ops::{Deref, DerefMut, Index};
+
+/// Scrambles the bits of a value, so that combining keys with XOR gives a good hash
+const fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// The Zobrist key of a value at a position. The hash of a grid is the XOR of the keys of all its
+/// cells, so changing a single cell only needs the old and the new key of that cell.
+fn cell_key<T: Hash>(position: usize, value: &T) -> u64 {
+    mix(fxhash::hash64(value) ^ mix(position as u64))
+}
+
+impl<T: Hash> Grid<T> {
+    /// A hash of the complete state of this grid, which is the same as [HashedGrid::state_hash]
+    pub fn state_hash(&self) -> u64 {
+        self.items
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (ix, value)| hash ^ cell_key(ix, value))
+    }
+
+    /// A fingerprint of the values in a row, which does not depend on the y coordinate. Rows
+    /// with the same content have the same fingerprint.
+    pub fn row_fingerprint(&self, y: i32) -> u64 {
+        self.east_line(y).fold(0, |hash, (location, value)| {
+            hash ^ cell_key(location.x() as usize, value)
+        })
+    }
+
+    /// A fingerprint of the values in a column, which does not depend on the x coordinate.
+    /// Columns with the same content have the same fingerprint.
+    pub fn column_fingerprint(&self, x: i32) -> u64 {
+        self.south_line(x).fold(0, |hash, (location, value)| {
+            hash ^ cell_key(location.y() as usize, value)
+        })
+    }
+}
+
+/// A grid that keeps a Zobrist hash of its state and fingerprints of its rows and columns up to
+/// date while it is being changed. This makes it cheap to detect cycles in a simulation without
+/// storing copies of the grid.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HashedGrid<T> {
+    grid: Grid<T>,
+    hash: u64,
+    rows: Vec<u64>,
+    columns: Vec<u64>,
+}
+
+impl<T: Hash> HashedGrid<T> {
+    pub fn new(grid: Grid<T>) -> Self {
+        let hash = grid.state_hash();
+        let rows = grid.y_range().map(|y| grid.row_fingerprint(y)).collect();
+        let columns = grid.x_range().map(|x| grid.column_fingerprint(x)).collect();
+        HashedGrid { grid, hash, rows, columns }
+    }
+
+    pub fn grid(&self) -> &Grid<T> { &self.grid }
+
+    pub fn into_grid(self) -> Grid<T> { self.grid }
+
+    pub fn state_hash(&self) -> u64 { self.hash }
+
+    pub fn row_fingerprint(&self, y: i32) -> u64 { self.rows[y as usize] }
+
+    pub fn column_fingerprint(&self, x: i32) -> u64 { self.columns[x as usize] }
+
+    /// Adds or removes the keys of the cell at the index, since XOR is its own inverse
+    fn toggle(&mut self, ix: usize) {
+        let width = self.grid.width() as usize;
+        let (x, y) = (ix % width, ix / width);
+        let value = &self.grid.items[ix];
+        self.hash ^= cell_key(ix, value);
+        self.rows[y] ^= cell_key(x, value);
+        self.columns[x] ^= cell_key(y, value);
+    }
+
+    pub fn get(&self, location: Location) -> Option<&T> { self.grid.get(location) }
+
+    /// Sets the value of a cell, returning the previous value or `None` if the location is not
+    /// on the grid.
+    pub fn set(&mut self, location: Location, value: T) -> Option<T> {
+        let ix = self.grid.index_from_location(location)?;
+        self.toggle(ix);
+        let previous = std::mem::replace(&mut self.grid.items[ix], value);
+        self.toggle(ix);
+        Some(previous)
+    }
+
+    pub fn swap(&mut self, first: Location, second: Location) {
+        if let Some(first_ix) = self.grid.index_from_location(first)
+            && let Some(second_ix) = self.grid.index_from_location(second)
+            && first_ix != second_ix
+        {
+            self.toggle(first_ix);
+            self.toggle(second_ix);
+            self.grid.items.swap(first_ix, second_ix);
+            self.toggle(first_ix);
+            self.toggle(second_ix);
+        }
+    }
+
+    /// Gives mutable access to a cell. The hash is updated when the returned guard is dropped.
+    pub fn get_mut(&mut self, location: Location) -> Option<CellGuard<'_, T>> {
+        let ix = self.grid.index_from_location(location)?;
+        self.toggle(ix);
+        Some(CellGuard { owner: self, ix })
+    }
+
+    /// Repeatedly calls `step` and yields the state hash after each step with its result. This
+    /// can be used directly with [crate::iter_utils::IteratorUtils::find_cyclic_result_at].
+    pub fn simulate<R, F>(mut self, mut step: F) -> impl Iterator<Item = (u64, R)>
+    where
+        F: FnMut(&mut HashedGrid<T>) -> R,
+    {
+        std::iter::from_fn(move || {
+            let result = step(&mut self);
+            Some((self.hash, result))
+        })
+    }
+}
+
+impl<T> Index<Location> for HashedGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: Location) -> &Self::Output { &self.grid[index] }
+}
+
+/// Mutable access to a single cell of a [HashedGrid], see [HashedGrid::get_mut]
+pub struct CellGuard<'a, T: Hash> {
+    owner: &'a mut HashedGrid<T>,
+    ix: usize,
+}
+
+impl<T: Hash> Deref for CellGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target { &self.owner.grid.items[self.ix] }
+}
+
+impl<T: Hash> DerefMut for CellGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.owner.grid.items[self.ix] }
+}
+
+impl<T: Hash> Drop for CellGuard<'_, T> {
+    fn drop(&mut self) { self.owner.toggle(self.ix) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HashedGrid;
+    use crate::direction::Direction::East;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use crate::iter_utils::IteratorUtils;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn incremental_hash_matches_full_hash() {
+        let mut grid = HashedGrid::new(parse("#..\n.O.\n..#"));
+        let start = grid.state_hash();
+
+        grid.swap(point2(1, 1), point2(1, 0));
+        assert_eq!(parse("#O.\n...\n..#").state_hash(), grid.state_hash());
+        *grid.get_mut(point2(2, 2)).unwrap() = 'O';
+        assert_eq!(Some('.'), grid.set(point2(0, 1), 'x'));
+        assert_eq!(grid.grid().state_hash(), grid.state_hash());
+        assert_ne!(start, grid.state_hash());
+
+        grid.set(point2(0, 1), '.');
+        *grid.get_mut(point2(2, 2)).unwrap() = '#';
+        grid.swap(point2(1, 1), point2(1, 0));
+        assert_eq!(start, grid.state_hash());
+    }
+
+    #[test]
+    fn row_and_column_fingerprints() {
+        let mut grid = HashedGrid::new(parse("ab\nba\nab"));
+        assert_eq!(grid.row_fingerprint(0), grid.row_fingerprint(2));
+        assert_ne!(grid.row_fingerprint(0), grid.row_fingerprint(1));
+        assert_ne!(grid.column_fingerprint(0), grid.column_fingerprint(1));
+
+        grid.set(point2(0, 1), 'a');
+        grid.set(point2(1, 1), 'b');
+        assert_eq!(grid.row_fingerprint(0), grid.row_fingerprint(1));
+        assert_eq!(
+            grid.grid().column_fingerprint(1),
+            grid.column_fingerprint(1)
+        );
+    }
+
+    #[test]
+    fn detect_cycle_in_simulation() {
+        let grid = HashedGrid::new(parse("O...."));
+        let position = grid
+            .simulate(|grid| {
+                let rock = grid.grid().find(|&c| c == 'O').unwrap();
+                let next = point2((rock + East).x() % 5, 0);
+                grid.swap(rock, next);
+                next.x()
+            })
+            .find_cyclic_result_at(1_000_000_002);
+        assert_eq!(Some(2), position);
+    }
+}
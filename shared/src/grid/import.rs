@@ -0,0 +1,133 @@
+use super::{Grid, GridCoord};
+use crate::geometry::vector2;
+use crate::parsing::separated_double_lines1;
+use image::RgbaImage;
+use nom::character::complete::multispace0;
+use nom::combinator::all_consuming;
+use nom::sequence::terminated;
+use nom::{Finish, Parser};
+use nom_parse_trait::ParseFrom;
+
+fn parse_text<'a, O>(
+    text: &'a str,
+    parser: impl Parser<&'a [u8], Output = O, Error = nom::error::Error<&'a [u8]>>,
+) -> O {
+    match all_consuming(terminated(parser, multispace0))
+        .parse_complete(text.as_bytes())
+        .finish()
+    {
+        Ok((_, result)) => result,
+        Err(e) => panic!(
+            "Error parsing grid, code: {:?}. Rest input:\n{}",
+            e.code,
+            String::from_utf8_lossy(&e.input[..e.input.len().min(100)])
+        ),
+    }
+}
+
+impl<T, C: GridCoord> Grid<T, C> {
+    /// Parses a grid from its text representation, e.g. for a test. Panics when the text is not a
+    /// valid grid.
+    pub fn from_ascii(text: &str) -> Grid<T, C>
+    where
+        T: for<'a> ParseFrom<&'a [u8]>,
+    {
+        parse_text(text, Grid::parse)
+    }
+
+    /// Parses multiple grids that are separated by empty lines
+    pub fn from_ascii_all(text: &str) -> Vec<Grid<T, C>>
+    where
+        T: for<'a> ParseFrom<&'a [u8]>,
+    {
+        parse_text(text, separated_double_lines1())
+    }
+
+    /// Reads a text file with one or more grids, separated by empty lines
+    pub fn load_all(filename: &str) -> Vec<Grid<T, C>>
+    where
+        T: for<'a> ParseFrom<&'a [u8]>,
+    {
+        let text = std::fs::read_to_string(filename).expect("Expect the file to be readable");
+        Grid::from_ascii_all(&text)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Reads an image where each pixel is a single cell, which is the reverse of
+    /// [Grid::render_to_image]. Any format that the `image` crate supports can be used.
+    pub fn from_image(filename: &str, mapping: impl Fn([u8; 4]) -> T) -> Grid<T> {
+        let image = image::open(filename).expect("Expect the image to be readable");
+        Grid::from_rgba_image(&image.to_rgba8(), 1, mapping)
+    }
+
+    /// Reads an image where each cell is a square of `scale` by `scale` pixels, which is the
+    /// reverse of [Grid::to_image]. Only the top-left pixel of each square is used.
+    pub fn from_rgba_image(
+        image: &RgbaImage,
+        scale: u32,
+        mapping: impl Fn([u8; 4]) -> T,
+    ) -> Grid<T> {
+        assert!(scale > 0, "Scale needs to be at least 1");
+        let (width, height) = (image.width() / scale, image.height() / scale);
+        let mut items = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                items.push(mapping(image.get_pixel(x * scale, y * scale).0));
+            }
+        }
+        Grid { items, size: vector2(width as i32, height as i32) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+
+    fn color(c: &char) -> [u8; 4] {
+        match c {
+            '#' => [0, 0, 0, 255],
+            'S' => [255, 0, 0, 255],
+            _ => [255, 255, 255, 255],
+        }
+    }
+
+    fn from_color(pixel: [u8; 4]) -> char {
+        match pixel {
+            [0, 0, 0, _] => '#',
+            [255, 0, 0, _] => 'S',
+            _ => '.',
+        }
+    }
+
+    #[test]
+    fn ascii_grids() {
+        let grid: Grid<char> = Grid::from_ascii("#.#\n.S.\n");
+        assert_eq!(point2(1, 1), grid.find(|&c| c == 'S').unwrap());
+
+        let grids: Vec<Grid<char, i64>> = Grid::from_ascii_all("#.\n.#\n\n###\n\n.\n.\n\n");
+        assert_eq!(3, grids.len());
+        assert_eq!(
+            vec![(2, 2), (3, 1), (1, 2)],
+            grids.iter().map(|g| (g.width(), g.height())).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error parsing grid")]
+    fn invalid_ascii_grid() { Grid::<char>::from_ascii("##\n#\n"); }
+
+    #[test]
+    fn image_round_trip() {
+        let grid: Grid<char> = Grid::from_ascii("#..\n.S#");
+        let image = grid.to_image(4, color);
+        assert_eq!(grid, Grid::from_rgba_image(&image, 4, from_color));
+
+        let filename = std::env::temp_dir().join("advent_lib_image_round_trip.png");
+        let filename = filename.to_str().unwrap();
+        grid.render_to_image(filename, color);
+        assert_eq!(grid, Grid::from_image(filename, from_color));
+        std::fs::remove_file(filename).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+use super::{GridCoord, Indexed, Location, Size, coord};
+use crate::direction::Direction;
+use crate::geometry::point2;
+use std::ops::{Index, IndexMut};
+
+/// The access to the cells of a grid that doesn't depend on how they are stored. The algorithms
+/// that only need this, like the lines, the flood fill and finding regions, are written once
+/// against it and shared by [super::Grid] and [super::TiledGrid].
+pub trait GridLayout:
+    Index<Location<Self::Coord>, Output = Self::Cell> + IndexMut<Location<Self::Coord>>
+{
+    type Cell;
+    type Coord: GridCoord;
+
+    fn layout_size(&self) -> Size<Self::Coord>;
+
+    /// # Safety
+    ///
+    /// This method does not do any boundary checks, so only use this if you already know that
+    /// the location is on the grid.
+    unsafe fn cell_unchecked(&self, location: Location<Self::Coord>) -> &Self::Cell;
+
+    /// # Safety
+    ///
+    /// This method does not do any boundary checks, so only use this if you already know that
+    /// the location is on the grid.
+    unsafe fn cell_unchecked_mut(&mut self, location: Location<Self::Coord>) -> &mut Self::Cell;
+
+    fn contains_location(&self, location: &Location<Self::Coord>) -> bool {
+        let size = self.layout_size();
+        (Self::Coord::default()..size.x()).contains(&location.x())
+            && (Self::Coord::default()..size.y()).contains(&location.y())
+    }
+
+    fn cell(&self, location: Location<Self::Coord>) -> Option<&Self::Cell> {
+        // Safety: the location has just been checked
+        self.contains_location(&location)
+            .then(|| unsafe { self.cell_unchecked(location) })
+    }
+
+    fn cell_mut(&mut self, location: Location<Self::Coord>) -> Option<&mut Self::Cell> {
+        if self.contains_location(&location) {
+            // Safety: the location has just been checked
+            Some(unsafe { self.cell_unchecked_mut(location) })
+        } else {
+            None
+        }
+    }
+}
+
+/// All locations of a grid of the size, in row order
+pub(super) fn row_order<C: GridCoord>(size: Size<C>) -> impl Iterator<Item = Location<C>> {
+    Indexed::new(0..(size.x() * size.y()).to_index(), size.x()).map(|(location, _)| location)
+}
+
+/// All cells with their location in row order
+pub(super) fn entries<G: GridLayout>(
+    cells: &G,
+) -> impl Iterator<Item = (Location<G::Coord>, &G::Cell)> {
+    row_order(cells.layout_size()).map(|location| (location, &cells[location]))
+}
+
+/// The location one step in the direction, if that is still on the grid
+pub(super) fn neighbour<G: GridLayout>(
+    cells: &G,
+    location: Location<G::Coord>,
+    direction: Direction,
+) -> Option<Location<G::Coord>> {
+    let (dx, dy) = coord::direction_steps(direction);
+    let next = point2(
+        location.x().checked_step(dx)?,
+        location.y().checked_step(dy)?,
+    );
+    cells.contains_location(&next).then_some(next)
+}
+
+pub(super) fn direct_neighbours<G: GridLayout>(
+    cells: &G,
+    location: Location<G::Coord>,
+) -> impl Iterator<Item = (Direction, &G::Cell)> {
+    Direction::ALL.into_iter().flat_map(move |dir| {
+        let next = neighbour(cells, location, dir)?;
+        cells.cell(next).map(|p| (dir, p))
+    })
+}
+
+/// Replaces the area of equal cells around the start with the value, using a scanline fill.
+/// Returns the number of cells that have been filled.
+pub(super) fn fill<G>(cells: &mut G, start: Location<G::Coord>, value: G::Cell) -> usize
+where
+    G: GridLayout,
+    G::Cell: PartialEq + Clone,
+{
+    let accept_cell = if let Some(cell) = cells.cell(start) {
+        cell.clone()
+    } else {
+        return 0; // Not in range, just return
+    };
+
+    let size = cells.layout_size();
+    let (zero, one) = (G::Coord::default(), G::Coord::ONE);
+    let mut stack = Vec::with_capacity(size.y().to_index());
+    stack.push(start);
+    let mut count_cells = 0usize;
+
+    while !stack.is_empty() {
+        let [x, y] = stack.pop().unwrap().coords;
+        let mut lx = x;
+        while lx > zero {
+            // Safety: we know that (x,y) is in bounds and we check lx explicitly
+            let fill_cell = unsafe { cells.cell_unchecked_mut(point2(lx - one, y)) };
+            if *fill_cell == accept_cell {
+                *fill_cell = value.clone();
+                count_cells += 1;
+                lx = lx - one;
+            } else {
+                break;
+            }
+        }
+
+        let mut rx = x;
+        while rx < size.x() {
+            // Safety: we know that (x,y) is in bounds and we check rx explicitly
+            let fill_cell = unsafe { cells.cell_unchecked_mut(point2(rx, y)) };
+            if *fill_cell == accept_cell {
+                *fill_cell = value.clone();
+                count_cells += 1;
+                rx = rx + one;
+            } else {
+                break;
+            }
+        }
+
+        let mut scan_next_fill = |y| {
+            let mut span_added = false;
+            for x in coord::coords(lx..rx) {
+                let check_cell = unsafe { cells.cell_unchecked(point2(x, y)) };
+                if *check_cell != accept_cell {
+                    span_added = false;
+                } else if !span_added {
+                    stack.push(point2(x, y));
+                    span_added = true;
+                }
+            }
+        };
+
+        if y > zero {
+            scan_next_fill(y - one);
+        }
+        if y + one < size.y() {
+            scan_next_fill(y + one);
+        }
+    }
+
+    count_cells
+}
+
+/// Iterates over a single row or column. The north and west lines keep the coordinate just after
+/// the next cell, so they stop at 0 without going negative.
+pub enum LineIterator<'a, G: GridLayout> {
+    North { grid: &'a G, x: G::Coord, y: G::Coord },
+    East { grid: &'a G, x: G::Coord, y: G::Coord },
+    South { grid: &'a G, x: G::Coord, y: G::Coord },
+    West { grid: &'a G, x: G::Coord, y: G::Coord },
+}
+
+impl<'a, G: GridLayout> LineIterator<'a, G> {
+    pub fn north(grid: &'a G, x: G::Coord) -> Self {
+        LineIterator::North { grid, x, y: grid.layout_size().y() }
+    }
+
+    pub fn east(grid: &'a G, y: G::Coord) -> Self {
+        LineIterator::East { grid, x: G::Coord::default(), y }
+    }
+
+    pub fn south(grid: &'a G, x: G::Coord) -> Self {
+        LineIterator::South { grid, x, y: G::Coord::default() }
+    }
+
+    pub fn west(grid: &'a G, y: G::Coord) -> Self {
+        LineIterator::West { grid, x: grid.layout_size().x(), y }
+    }
+}
+
+impl<'a, G: GridLayout> Iterator for LineIterator<'a, G> {
+    type Item = (Location<G::Coord>, &'a G::Cell);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (zero, one) = (G::Coord::default(), G::Coord::ONE);
+        let (grid, location) = match self {
+            LineIterator::North { grid, x, y } => {
+                if *y <= zero {
+                    return None;
+                }
+                *y = *y - one;
+                (grid, point2(*x, *y))
+            }
+            LineIterator::East { grid, x, y } => {
+                if *x >= grid.layout_size().x() {
+                    return None;
+                }
+                *x = *x + one;
+                (grid, point2(*x - one, *y))
+            }
+            LineIterator::South { grid, x, y } => {
+                if *y >= grid.layout_size().y() {
+                    return None;
+                }
+                *y = *y + one;
+                (grid, point2(*x, *y - one))
+            }
+            LineIterator::West { grid, x, y } => {
+                if *x <= zero {
+                    return None;
+                }
+                *x = *x - one;
+                (grid, point2(*x, *y))
+            }
+        };
+        // Safety: the location has been checked against the size of the grid
+        Some((location, unsafe { grid.cell_unchecked(location) }))
+    }
+}
+
+/// Iterates over all rows or columns of a grid, each as a [LineIterator]
+pub enum LinesIterator<'a, G: GridLayout> {
+    North { grid: &'a G, x: G::Coord },
+    East { grid: &'a G, y: G::Coord },
+    South { grid: &'a G, x: G::Coord },
+    West { grid: &'a G, y: G::Coord },
+}
+
+impl<'a, G: GridLayout> LinesIterator<'a, G> {
+    pub fn north(grid: &'a G) -> Self { LinesIterator::North { grid, x: G::Coord::default() } }
+
+    pub fn east(grid: &'a G) -> Self { LinesIterator::East { grid, y: G::Coord::default() } }
+
+    pub fn south(grid: &'a G) -> Self { LinesIterator::South { grid, x: G::Coord::default() } }
+
+    pub fn west(grid: &'a G) -> Self { LinesIterator::West { grid, y: G::Coord::default() } }
+}
+
+impl<'a, G: GridLayout> Iterator for LinesIterator<'a, G> {
+    type Item = LineIterator<'a, G>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let one = G::Coord::ONE;
+        match self {
+            LinesIterator::North { grid, x } => {
+                if *x >= grid.layout_size().x() {
+                    return None;
+                }
+                *x = *x + one;
+                Some(LineIterator::north(grid, *x - one))
+            }
+            LinesIterator::East { grid, y } => {
+                if *y >= grid.layout_size().y() {
+                    return None;
+                }
+                *y = *y + one;
+                Some(LineIterator::east(grid, *y - one))
+            }
+            LinesIterator::South { grid, x } => {
+                if *x >= grid.layout_size().x() {
+                    return None;
+                }
+                *x = *x + one;
+                Some(LineIterator::south(grid, *x - one))
+            }
+            LinesIterator::West { grid, y } => {
+                if *y >= grid.layout_size().y() {
+                    return None;
+                }
+                *y = *y + one;
+                Some(LineIterator::west(grid, *y - one))
+            }
+        }
+    }
+}
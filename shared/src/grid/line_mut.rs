@@ -0,0 +1,264 @@
+use super::Grid;
+use std::cmp::Ordering;
+use std::ops::{Index, IndexMut};
+
+/// Mutable access to all cells of a single row or column, in the order of the direction of the
+/// line. E.g. a north line starts at the bottom of the grid.
+pub struct LineMut<'a, T> {
+    cells: Vec<&'a mut T>,
+}
+
+impl<'a, T> LineMut<'a, T> {
+    pub fn len(&self) -> usize { self.cells.len() }
+
+    pub fn is_empty(&self) -> bool { self.cells.is_empty() }
+
+    pub fn get(&self, index: usize) -> Option<&T> { self.cells.get(index).map(|cell| &**cell) }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.cells.get_mut(index).map(|cell| &mut **cell)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> { self.cells.iter().map(|cell| &**cell) }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut().map(|cell| &mut **cell)
+    }
+
+    /// Swaps the values of two cells in the line
+    pub fn swap(&mut self, first: usize, second: usize) {
+        if first == second {
+            return;
+        }
+        let (low, high) = (first.min(second), first.max(second));
+        let (left, right) = self.cells.split_at_mut(high);
+        std::mem::swap(left[low], right[0]);
+    }
+
+    pub fn reverse(&mut self) {
+        let len = self.len();
+        for ix in 0..len / 2 {
+            self.swap(ix, len - 1 - ix);
+        }
+    }
+
+    fn reverse_range(&mut self, from: usize, to: usize) {
+        for ix in 0..(to - from) / 2 {
+            self.swap(from + ix, to - 1 - ix);
+        }
+    }
+
+    /// Moves all values `amount` places towards the start of the line, wrapping around
+    pub fn rotate_left(&mut self, amount: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let amount = amount % self.len();
+        self.reverse_range(0, amount);
+        self.reverse_range(amount, self.len());
+        self.reverse();
+    }
+
+    /// Moves all values `amount` places towards the end of the line, wrapping around
+    pub fn rotate_right(&mut self, amount: usize) {
+        if self.is_empty() {
+            return;
+        }
+        self.rotate_left(self.len() - amount % self.len());
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, key: F)
+    where
+        K: Ord,
+        F: Fn(&T) -> K,
+    {
+        self.sort_by(|first, second| key(first).cmp(&key(second)));
+    }
+
+    /// Sorts the values of the line (stable), in the order of the line
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let mut order = (0..self.len()).collect::<Vec<_>>();
+        order.sort_by(|&first, &second| compare(self.cells[first], self.cells[second]));
+
+        // Follow each cycle of the permutation, swapping the values into place
+        let mut placed = vec![false; order.len()];
+        for start in 0..order.len() {
+            let mut current = start;
+            while !placed[current] {
+                placed[current] = true;
+                let next = order[current];
+                if !placed[next] {
+                    self.swap(current, next);
+                }
+                current = next;
+            }
+        }
+    }
+
+    /// Replaces the values in the line with the given values, in order. When there are fewer
+    /// values than cells, the rest of the line is unchanged.
+    pub fn replace(&mut self, values: impl IntoIterator<Item = T>) {
+        for (cell, value) in self.cells.iter_mut().zip(values) {
+            **cell = value;
+        }
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.replace(std::iter::repeat_n(value, self.len()));
+    }
+}
+
+impl<T> Index<usize> for LineMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output { self.cells[index] }
+}
+
+impl<T> IndexMut<usize> for LineMut<'_, T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output { self.cells[index] }
+}
+
+impl<T> Grid<T> {
+    fn columns_mut(&mut self) -> Vec<LineMut<'_, T>> {
+        let width = self.width() as usize;
+        let mut columns = (0..width)
+            .map(|_| LineMut { cells: Vec::with_capacity(self.height() as usize) })
+            .collect::<Vec<_>>();
+        for (ix, cell) in self.items.iter_mut().enumerate() {
+            columns[ix % width].cells.push(cell);
+        }
+        columns
+    }
+
+    fn rows_mut(&mut self) -> impl Iterator<Item = LineMut<'_, T>> {
+        let width = self.width().max(1) as usize;
+        self.items
+            .chunks_mut(width)
+            .map(|row| LineMut { cells: row.iter_mut().collect() })
+    }
+
+    /// All columns, each going from the bottom to the top
+    pub fn north_lines_mut(&mut self) -> impl Iterator<Item = LineMut<'_, T>> {
+        self.columns_mut().into_iter().map(|mut line| {
+            line.cells.reverse();
+            line
+        })
+    }
+
+    /// All rows, each going from the left to the right
+    pub fn east_lines_mut(&mut self) -> impl Iterator<Item = LineMut<'_, T>> { self.rows_mut() }
+
+    /// All columns, each going from the top to the bottom
+    pub fn south_lines_mut(&mut self) -> impl Iterator<Item = LineMut<'_, T>> {
+        self.columns_mut().into_iter()
+    }
+
+    /// All rows, each going from the right to the left
+    pub fn west_lines_mut(&mut self) -> impl Iterator<Item = LineMut<'_, T>> {
+        self.rows_mut().map(|mut line| {
+            line.cells.reverse();
+            line
+        })
+    }
+
+    pub fn north_line_mut(&mut self, x: i32) -> LineMut<'_, T> {
+        let mut line = self.south_line_mut(x);
+        line.cells.reverse();
+        line
+    }
+
+    pub fn east_line_mut(&mut self, y: i32) -> LineMut<'_, T> {
+        assert!(self.y_range().contains(&y), "Row {y} is not on the grid");
+        self.rows_mut().nth(y as usize).unwrap()
+    }
+
+    pub fn south_line_mut(&mut self, x: i32) -> LineMut<'_, T> {
+        assert!(self.x_range().contains(&x), "Column {x} is not on the grid");
+        let width = self.width() as usize;
+        LineMut { cells: self.items.iter_mut().skip(x as usize).step_by(width).collect() }
+    }
+
+    pub fn west_line_mut(&mut self, y: i32) -> LineMut<'_, T> {
+        let mut line = self.east_line_mut(y);
+        line.cells.reverse();
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn rotate_rows_and_columns() {
+        let mut screen = Grid::new_default('.', 7, 3);
+        for y in 0..2 {
+            screen.east_line_mut(y).replace(['#'; 3]);
+        }
+        screen.south_line_mut(1).rotate_right(1);
+        screen.east_line_mut(0).rotate_right(4);
+        screen.south_line_mut(1).rotate_right(1);
+        assert_eq!(parse(".#..#.#\n#.#....\n.#....."), screen);
+
+        screen.west_line_mut(1).rotate_right(2);
+        assert_eq!(parse(".#..#.#\n#....#.\n.#....."), screen);
+        screen.north_line_mut(0).rotate_left(1);
+        assert_eq!(parse(".#..#.#\n.....#.\n##....."), screen);
+    }
+
+    #[test]
+    fn sort_and_reverse_lines() {
+        let mut grid = parse("dbca\nhgfe");
+        for mut line in grid.east_lines_mut() {
+            line.sort();
+        }
+        assert_eq!(parse("abcd\nefgh"), grid);
+
+        for mut line in grid.west_lines_mut() {
+            line.sort_by_key(|&c| c == 'b' || c == 'e');
+        }
+        assert_eq!(parse("bacd\nefgh"), grid);
+
+        for mut line in grid.north_lines_mut() {
+            line.reverse();
+        }
+        assert_eq!(parse("efgh\nbacd"), grid);
+    }
+
+    #[test]
+    fn mutate_columns() {
+        let mut grid = parse("#..\n...\n#.#");
+        for mut line in grid.south_lines_mut() {
+            let count = line.iter().filter(|&&c| c == '#').count();
+            line.fill('.');
+            for ix in 0..count {
+                line[ix] = '#';
+            }
+        }
+        assert_eq!(parse("#.#\n#..\n..."), grid);
+        assert_eq!(Some(&'#'), grid.north_line_mut(0).get(1));
+        *grid.north_line_mut(2).get_mut(0).unwrap() = 'x';
+        assert_eq!('x', grid[point2(2, 2)]);
+    }
+}
@@ -0,0 +1,342 @@
+use super::{Grid, Location, Size};
+use bit_vec::BitVec;
+use std::fmt::{Debug, Formatter};
+use std::ops::{Index, IndexMut};
+
+fn index_of(size: Size, location: Location) -> Option<usize> {
+    if (0..size.x()).contains(&location.x()) && (0..size.y()).contains(&location.y()) {
+        Some((location.x() + location.y() * size.x()) as usize)
+    } else {
+        None
+    }
+}
+
+fn expect_index(size: Size, location: Location) -> usize {
+    index_of(size, location)
+        .unwrap_or_else(|| panic!("Location {location:?} is not on a grid of size {size:?}"))
+}
+
+/// A set of locations on a grid, stored as a single bit per cell. This replaces a hash set when
+/// the size of the grid is known. Iterating over the set gives the locations in row order.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LocationSet {
+    bits: BitVec,
+    size: Size,
+}
+
+impl LocationSet {
+    pub fn new(size: Size) -> LocationSet {
+        LocationSet { bits: BitVec::from_elem((size.x() * size.y()) as usize, false), size }
+    }
+
+    pub fn for_grid<T>(grid: &Grid<T>) -> LocationSet { LocationSet::new(grid.size()) }
+
+    pub fn size(&self) -> Size { self.size }
+
+    pub fn len(&self) -> usize { self.bits.count_ones() as usize }
+
+    pub fn is_empty(&self) -> bool { self.bits.none() }
+
+    /// Checks if the location is in the set, which is never the case outside the grid
+    pub fn contains(&self, location: &Location) -> bool {
+        index_of(self.size, *location).is_some_and(|ix| self.bits[ix])
+    }
+
+    /// Adds the location, returning `true` if it was not in the set yet. Panics when the location
+    /// is not on the grid.
+    pub fn insert(&mut self, location: Location) -> bool {
+        let ix = expect_index(self.size, location);
+        let added = !self.bits[ix];
+        self.bits.set(ix, true);
+        added
+    }
+
+    /// Removes the location, returning `true` if it was in the set
+    pub fn remove(&mut self, location: &Location) -> bool {
+        match index_of(self.size, *location) {
+            Some(ix) if self.bits[ix] => {
+                self.bits.set(ix, false);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear(&mut self) { self.bits.clear() }
+
+    pub fn iter(&self) -> impl Iterator<Item = Location> + '_ {
+        let size = self.size;
+        self.bits
+            .iter()
+            .enumerate()
+            .filter(|&(_, present)| present)
+            .map(move |(ix, _)| Location::from((size, ix)))
+    }
+
+    fn assert_same_size(&self, other: &LocationSet) {
+        assert_eq!(
+            self.size, other.size,
+            "Location sets need to be of the same size"
+        );
+    }
+
+    /// Adds all locations of the other set, returns `true` if this set changed
+    pub fn union_with(&mut self, other: &LocationSet) -> bool {
+        self.assert_same_size(other);
+        self.bits.or(&other.bits)
+    }
+
+    /// Keeps only the locations that are also in the other set, returns `true` if this set changed
+    pub fn intersect_with(&mut self, other: &LocationSet) -> bool {
+        self.assert_same_size(other);
+        self.bits.and(&other.bits)
+    }
+
+    /// Removes all locations of the other set, returns `true` if this set changed
+    pub fn difference_with(&mut self, other: &LocationSet) -> bool {
+        self.assert_same_size(other);
+        self.bits.difference(&other.bits)
+    }
+
+    pub fn union(&self, other: &LocationSet) -> LocationSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    pub fn intersection(&self, other: &LocationSet) -> LocationSet {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    pub fn difference(&self, other: &LocationSet) -> LocationSet {
+        let mut result = self.clone();
+        result.difference_with(other);
+        result
+    }
+
+    pub fn is_subset(&self, other: &LocationSet) -> bool {
+        self.assert_same_size(other);
+        self.bits
+            .blocks()
+            .zip(other.bits.blocks())
+            .all(|(own, other)| own & !other == 0)
+    }
+}
+
+impl Extend<Location> for LocationSet {
+    fn extend<I: IntoIterator<Item = Location>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|location| {
+            self.insert(location);
+        });
+    }
+}
+
+impl Debug for LocationSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A map from locations on a grid to values, stored as a dense vector with an optional value for
+/// each cell. Iterating over the map gives the entries in row order.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LocationMap<V> {
+    values: Vec<Option<V>>,
+    size: Size,
+    len: usize,
+}
+
+impl<V> LocationMap<V> {
+    pub fn new(size: Size) -> LocationMap<V> {
+        let mut values = Vec::new();
+        values.resize_with((size.x() * size.y()) as usize, || None);
+        LocationMap { values, size, len: 0 }
+    }
+
+    pub fn for_grid<T>(grid: &Grid<T>) -> LocationMap<V> { LocationMap::new(grid.size()) }
+
+    pub fn size(&self) -> Size { self.size }
+
+    pub fn len(&self) -> usize { self.len }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    pub fn contains_key(&self, location: &Location) -> bool { self.get(location).is_some() }
+
+    pub fn get(&self, location: &Location) -> Option<&V> {
+        self.values.get(index_of(self.size, *location)?)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, location: &Location) -> Option<&mut V> {
+        self.values.get_mut(index_of(self.size, *location)?)?.as_mut()
+    }
+
+    /// Sets the value for the location, returning the previous value. Panics when the location is
+    /// not on the grid.
+    pub fn insert(&mut self, location: Location, value: V) -> Option<V> {
+        let previous = self.values[expect_index(self.size, location)].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, location: &Location) -> Option<V> {
+        let previous = self.values.get_mut(index_of(self.size, *location)?)?.take();
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    pub fn get_or_insert_with(
+        &mut self,
+        location: Location,
+        default: impl FnOnce() -> V,
+    ) -> &mut V {
+        let slot = &mut self.values[expect_index(self.size, location)];
+        if slot.is_none() {
+            self.len += 1;
+        }
+        slot.get_or_insert_with(default)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(|value| *value = None);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Location, &V)> {
+        let size = self.size;
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(move |(ix, value)| Some((Location::from((size, ix)), value.as_ref()?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Location> + '_ {
+        self.iter().map(|(location, _)| location)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> { self.values.iter().flatten() }
+
+    /// The set of all locations that have a value
+    pub fn key_set(&self) -> LocationSet {
+        let bits = self.values.iter().map(Option::is_some).collect();
+        LocationSet { bits, size: self.size }
+    }
+}
+
+impl<V> Index<Location> for LocationMap<V> {
+    type Output = V;
+
+    fn index(&self, index: Location) -> &Self::Output {
+        self.get(&index).unwrap_or_else(|| panic!("No value for location {index:?}"))
+    }
+}
+
+impl<V> IndexMut<Location> for LocationMap<V> {
+    fn index_mut(&mut self, index: Location) -> &mut Self::Output {
+        self.get_mut(&index)
+            .unwrap_or_else(|| panic!("No value for location {index:?}"))
+    }
+}
+
+impl<V: Debug> Debug for LocationMap<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocationMap, LocationSet};
+    use crate::direction::Direction;
+    use crate::geometry::{point2, vector2};
+    use crate::grid::Grid;
+    use crate::search::breadth_first_search_visited;
+
+    #[test]
+    fn set_operations() {
+        let mut first = LocationSet::new(vector2(4, 3));
+        assert!(first.insert(point2(3, 0)));
+        assert!(first.insert(point2(1, 2)));
+        assert!(!first.insert(point2(3, 0)));
+        assert!(first.insert(point2(0, 1)));
+        assert_eq!(3, first.len());
+        assert!(!first.contains(&point2(-1, 0)));
+        assert_eq!(
+            vec![point2(3, 0), point2(0, 1), point2(1, 2)],
+            first.iter().collect::<Vec<_>>()
+        );
+
+        let mut second = LocationSet::new(vector2(4, 3));
+        second.extend([point2(0, 1), point2(2, 2)]);
+        assert_eq!(4, first.union(&second).len());
+        assert_eq!(
+            vec![point2(0, 1)],
+            first.intersection(&second).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(2, first.difference(&second).len());
+        assert!(first.intersection(&second).is_subset(&second));
+        assert!(!first.is_subset(&second));
+
+        assert!(first.remove(&point2(3, 0)));
+        assert!(!first.remove(&point2(3, 0)));
+        first.clear();
+        assert!(first.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not on a grid")]
+    fn insert_outside_grid() { LocationSet::new(vector2(2, 2)).insert(point2(2, 0)); }
+
+    #[test]
+    fn map_operations() {
+        let mut map = LocationMap::new(vector2(3, 3));
+        assert_eq!(None, map.insert(point2(2, 1), 'a'));
+        assert_eq!(None, map.insert(point2(0, 2), 'b'));
+        assert_eq!(Some('a'), map.insert(point2(2, 1), 'c'));
+        *map.get_or_insert_with(point2(1, 0), || 'd') = 'e';
+        assert_eq!(3, map.len());
+        assert_eq!('c', map[point2(2, 1)]);
+        assert_eq!(None, map.get(&point2(5, 5)));
+        assert_eq!(
+            vec![
+                (point2(1, 0), &'e'),
+                (point2(2, 1), &'c'),
+                (point2(0, 2), &'b')
+            ],
+            map.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![point2(1, 0), point2(2, 1), point2(0, 2)],
+            map.key_set().iter().collect::<Vec<_>>()
+        );
+
+        assert_eq!(Some('b'), map.remove(&point2(0, 2)));
+        assert_eq!(2, map.len());
+        assert_eq!("{Point(1,0): 'e', Point(2,1): 'c'}", format!("{map:?}"));
+    }
+
+    #[test]
+    fn visited_store_for_search() {
+        let grid: Grid<char> = Grid::from_ascii("..#.\n.##.\n....\n###.");
+        let mut visited = LocationSet::for_grid(&grid);
+        breadth_first_search_visited(
+            point2(0, 0),
+            |location| {
+                Direction::ALL
+                    .map(|dir| location + dir)
+                    .into_iter()
+                    .filter(|&next| grid.get(next) == Some(&'.'))
+            },
+            &mut visited,
+        );
+        assert_eq!(10, visited.len());
+        assert!(visited.contains(&point2(3, 0)));
+        assert!(!visited.contains(&point2(2, 0)));
+    }
+}
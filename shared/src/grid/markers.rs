@@ -0,0 +1,276 @@
+use super::{Grid, Location};
+use crate::geometry::{point2, vector2};
+use fxhash::FxHashMap;
+use nom::Err::Error;
+use nom::character::complete::{line_ending, not_line_ending};
+use nom::error::{ErrorKind, ParseError};
+use nom::multi::{many1, separated_list1};
+use nom::{AsBytes, AsChar, Compare, IResult, Input, Parser};
+use nom_parse_trait::ParseFrom;
+use std::hash::Hash;
+
+/// The locations of special cells (like the start and end of a maze) that have been taken out of
+/// a grid. The locations of each marker are kept in row order, and iterating gives the markers in
+/// the order of their first appearance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Markers<K: Eq + Hash> {
+    locations: FxHashMap<K, Vec<Location>>,
+}
+
+impl<K: Eq + Hash> Default for Markers<K> {
+    fn default() -> Self { Markers { locations: FxHashMap::default() } }
+}
+
+impl<K: Eq + Hash> Markers<K> {
+    fn add(&mut self, marker: K, location: Location) {
+        self.locations.entry(marker).or_default().push(location);
+    }
+
+    /// Returns the location of a marker that should appear exactly once
+    pub fn single(&self, marker: &K) -> Option<Location> {
+        match self.all(marker) {
+            [location] => Some(*location),
+            _ => None,
+        }
+    }
+
+    /// Returns all locations where the marker was found
+    pub fn all(&self, marker: &K) -> &[Location] {
+        self.locations.get(marker).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Vec<Location>)> {
+        let mut markers = self.locations.iter().collect::<Vec<_>>();
+        markers.sort_by_key(|(_, locations)| (locations[0].y(), locations[0].x()));
+        markers.into_iter()
+    }
+}
+
+impl<T> Grid<T> {
+    /// Replaces all cells that are one of the markers with the base value, and returns where they
+    /// were found. This is useful for cell enums that have variants for the start and end.
+    pub fn take_markers(&mut self, markers: &[T], base: T) -> Markers<T>
+    where
+        T: Eq + Hash + Clone,
+    {
+        let mut result = Markers::default();
+        for (ix, cell) in self.items.iter_mut().enumerate() {
+            if markers.contains(cell) {
+                let marker = std::mem::replace(cell, base.clone());
+                result.add(marker, Location::from((self.size, ix)));
+            }
+        }
+        result
+    }
+
+    /// Replaces all labels of two letters next to each other with the base value, and returns the
+    /// open cell next to each label, using its two letters as the marker. A label reads from left
+    /// to right or from top to bottom, like the portals of a donut maze. Labels without an open
+    /// cell next to them are still replaced, but not returned.
+    pub fn take_letter_pairs<L, O>(&mut self, is_letter: L, is_open: O, base: T) -> Markers<[T; 2]>
+    where
+        T: Eq + Hash + Clone,
+        L: Fn(&T) -> bool,
+        O: Fn(&T) -> bool,
+    {
+        let mut labels = Vec::new();
+        for (first, cell) in self.entries().filter(|(_, cell)| is_letter(cell)) {
+            for step in [vector2(1, 0), vector2(0, 1)] {
+                let second = first + step;
+                let Some(next) = self.get(second).filter(|next| is_letter(next)) else {
+                    continue;
+                };
+                let open = [first - step, second + step]
+                    .into_iter()
+                    .find(|&location| self.get(location).is_some_and(&is_open));
+                labels.push(([cell.clone(), next.clone()], [first, second], open));
+            }
+        }
+
+        let mut result = Markers::default();
+        for (label, letters, open) in labels {
+            letters.into_iter().for_each(|location| self[location] = base.clone());
+            if let Some(open) = open {
+                result.add(label, open);
+            }
+        }
+        for locations in result.locations.values_mut() {
+            locations.sort_by_key(|location| (location.y(), location.x()));
+        }
+        result
+    }
+}
+
+enum Cell<T> {
+    Value(T),
+    Marker(char),
+}
+
+fn marked_line<I, E, T>(markers: &'static str) -> impl Parser<I, Output = Vec<Cell<T>>, Error = E>
+where
+    T: ParseFrom<I, E>,
+    E: ParseError<I>,
+    I: AsBytes + Input,
+    <I as Input>::Item: AsChar,
+    I: Compare<&'static str>,
+{
+    let cell = move |input: I| -> IResult<I, Cell<T>, E> {
+        // The length of the element is in bytes for text input, and always 1 for byte input
+        match input.iter_elements().next().map(|item| (item.as_char(), item.len())) {
+            Some((c, len)) if markers.contains(c) => Ok((input.take_from(len), Cell::Marker(c))),
+            _ => T::parse(input).map(|(rest, value)| (rest, Cell::Value(value))),
+        }
+    };
+    not_line_ending.and_then(many1(cell))
+}
+
+fn build_marked_grid<T>(lines: Vec<Vec<Cell<T>>>, width: usize, base: T) -> (Grid<T>, Markers<char>)
+where
+    T: Clone + Default,
+{
+    let mut grid = Grid::new_empty(width as i32, lines.len() as i32);
+    let mut markers = Markers::default();
+    for (y, line) in lines.into_iter().enumerate() {
+        for (x, cell) in line.into_iter().enumerate() {
+            let location = point2(x as i32, y as i32);
+            grid[location] = match cell {
+                Cell::Value(value) => value,
+                Cell::Marker(marker) => {
+                    markers.add(marker, location);
+                    base.clone()
+                }
+            };
+        }
+    }
+    (grid, markers)
+}
+
+/// Parses a grid like [Grid::parse], but any of the `markers` characters are not parsed as a cell.
+/// Their locations are collected instead and the `base` value is put in the grid.
+pub fn marked_grid_parser<I, E, T>(
+    markers: &'static str,
+    base: T,
+) -> impl Parser<I, Output = (Grid<T>, Markers<char>), Error = E>
+where
+    T: ParseFrom<I, E> + Clone + Default,
+    E: ParseError<I>,
+    I: AsBytes + Input,
+    <I as Input>::Item: AsChar,
+    I: Compare<&'static str>,
+{
+    move |input: I| {
+        let (rest, lines) = separated_list1(line_ending, marked_line(markers)).parse(input)?;
+        let width = lines[0].len();
+        if lines.iter().any(|line| line.len() != width) {
+            return Err(Error(E::from_error_kind(rest, ErrorKind::LengthValue)));
+        }
+        Ok((rest, build_marked_grid(lines, width, base.clone())))
+    }
+}
+
+/// The same as [marked_grid_parser], but allows for uneven lines like
+/// [super::uneven_grid_parser], which are padded with the default value.
+pub fn uneven_marked_grid_parser<I, E, T>(
+    markers: &'static str,
+    base: T,
+) -> impl Parser<I, Output = (Grid<T>, Markers<char>), Error = E>
+where
+    T: ParseFrom<I, E> + Clone + Default,
+    E: ParseError<I>,
+    I: AsBytes + Input,
+    <I as Input>::Item: AsChar,
+    I: Compare<&'static str>,
+{
+    move |input: I| {
+        let (rest, lines) = separated_list1(line_ending, marked_line(markers)).parse(input)?;
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        Ok((rest, build_marked_grid(lines, width, base.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_macros::FromRepr;
+    use nom::Finish;
+
+    #[repr(u8)]
+    #[derive(FromRepr, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Block {
+        #[default]
+        Empty = b'.',
+        Wall = b'#',
+        Start = b'S',
+        End = b'E',
+    }
+
+    #[test]
+    fn take_markers_from_enum_grid() {
+        let result: Result<Grid<Block>, nom::error::Error<_>> =
+            nom_parse_trait::ParseFromExt::parse_complete("#S.\n#.E");
+        let mut grid = result.unwrap();
+        let markers = grid.take_markers(&[Block::Start, Block::End], Block::Empty);
+        assert_eq!(Some(point2(1, 0)), markers.single(&Block::Start));
+        assert_eq!(Some(point2(2, 1)), markers.single(&Block::End));
+        assert_eq!(None, grid.find(|&b| b == Block::Start));
+    }
+
+    #[test]
+    fn take_portal_letter_pairs() {
+        let mut grid: Grid<char> =
+            Grid::from_ascii("  A   \n  A   \n##.###\n##..BC\n##.###\n##B###\n  C   ");
+        let portals = grid.take_letter_pairs(char::is_ascii_uppercase, |&c| c == '.', ' ');
+        assert_eq!(Some(point2(2, 2)), portals.single(&['A', 'A']));
+        assert_eq!(&[point2(3, 3), point2(2, 4)], portals.all(&['B', 'C']));
+        assert_eq!(None, grid.find(|c| c.is_ascii_uppercase()));
+        assert_eq!('.', grid[point2(2, 4)]);
+    }
+
+    #[test]
+    fn parse_with_marker_characters() {
+        let result = marked_grid_parser::<_, nom::error::Error<&str>, Block>("ab", Block::Empty)
+            .parse_complete("#a.\nb.a")
+            .finish();
+        let (_, (grid, markers)) = result.unwrap();
+        assert_eq!(&[point2(1, 0), point2(2, 1)], markers.all(&'a'));
+        assert_eq!(Some(point2(0, 1)), markers.single(&'b'));
+        assert_eq!(None, markers.single(&'a'));
+        assert_eq!(Block::Empty, grid[point2(0, 1)]);
+        assert_eq!(Block::Wall, grid[point2(0, 0)]);
+        assert_eq!(
+            vec![&'a', &'b'],
+            markers.iter().map(|(marker, _)| marker).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parse_multi_byte_markers() {
+        let result = marked_grid_parser::<_, nom::error::Error<&str>, Block>("█λ", Block::Empty)
+            .parse_complete("#λ.\n█.#")
+            .finish();
+        let (_, (grid, markers)) = result.unwrap();
+        assert_eq!(Some(point2(1, 0)), markers.single(&'λ'));
+        assert_eq!(Some(point2(0, 1)), markers.single(&'█'));
+        assert_eq!(Block::Wall, grid[point2(2, 1)]);
+        assert_eq!(
+            vec![&'λ', &'█'],
+            markers.iter().map(|(marker, _)| marker).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parse_uneven_with_markers() {
+        let mut parser =
+            uneven_marked_grid_parser::<_, nom::error::Error<&str>, Block>("X", Block::Wall);
+        let (_, (grid, markers)) = parser.parse_complete("X\n..X.").finish().unwrap();
+        assert_eq!(4, grid.width());
+        assert_eq!(Block::Wall, grid[point2(0, 0)]);
+        assert_eq!(Block::Empty, grid[point2(3, 0)]);
+        assert_eq!(2, markers.all(&'X').len());
+        assert!(
+            marked_grid_parser::<_, nom::error::Error<&str>, Block>("X", Block::Wall)
+                .parse_complete("X\n..X.")
+                .is_err()
+        );
+    }
+}
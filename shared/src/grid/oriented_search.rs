@@ -0,0 +1,234 @@
+use super::{Grid, Location};
+use crate::direction::Direction;
+use crate::search::{SearchGraph, SearchGraphWithGoal, a_star_search_from};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+/// A node in an [OrientedSearch]: where we are, which way we are facing and any extra state
+/// that is needed to determine the next moves (e.g. how many steps were taken in a straight line).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Heading<S> {
+    pub location: Location,
+    pub direction: Direction,
+    pub state: S,
+}
+
+impl<S> Heading<S> {
+    pub fn new(location: Location, direction: Direction, state: S) -> Self {
+        Heading { location, direction, state }
+    }
+}
+
+/// The turn that is made relative to the current direction before taking a step
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Straight,
+    Left,
+    Right,
+    Reverse,
+}
+
+impl Turn {
+    pub const ALL: [Turn; 4] = [Turn::Straight, Turn::Left, Turn::Right, Turn::Reverse];
+
+    /// Going straight or turning left or right, but never turning around
+    pub const FORWARD: [Turn; 3] = [Turn::Straight, Turn::Left, Turn::Right];
+
+    pub const fn apply(self, direction: Direction) -> Direction {
+        match self {
+            Turn::Straight => direction,
+            Turn::Left => direction.turn_left(),
+            Turn::Right => direction.turn_right(),
+            Turn::Reverse => direction.turn_left().turn_left(),
+        }
+    }
+}
+
+/// The result of an [OrientedSearch]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrientedPath<S, C> {
+    pub cost: C,
+    /// All headings from the start up to and including the goal
+    pub path: Vec<Heading<S>>,
+}
+
+/// A search through a grid where each node is a [Heading]. Created using
+/// [Grid::oriented_search], which describes how moves are made.
+pub struct OrientedSearch<'a, T, S, C, FM, FG, FH> {
+    grid: &'a Grid<T>,
+    turns: Vec<Turn>,
+    move_cost: FM,
+    goal: FG,
+    heuristic: FH,
+    _types: PhantomData<(S, C)>,
+}
+
+impl<T> Grid<T> {
+    /// Starts building a search where each node has a location, a direction and some state `S`.
+    /// For each allowed [Turn], the `move_cost` function is called with the current heading, the
+    /// turn and the value of the cell that would be entered. It returns the new state and the
+    /// cost of the move, or `None` when the move is not allowed.
+    #[allow(clippy::type_complexity)]
+    pub fn oriented_search<S, C, FM>(
+        &self,
+        move_cost: FM,
+    ) -> OrientedSearch<'_, T, S, C, FM, fn(&Heading<S>) -> bool, fn(&Heading<S>) -> C>
+    where
+        FM: Fn(&Heading<S>, Turn, &T) -> Option<(S, C)>,
+        C: Default,
+    {
+        OrientedSearch {
+            grid: self,
+            turns: Turn::FORWARD.to_vec(),
+            move_cost,
+            goal: |_| false,
+            heuristic: |_| C::default(),
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, S, C, FM, FG, FH> OrientedSearch<'a, T, S, C, FM, FG, FH>
+where
+    S: Copy + Eq + Hash,
+    C: Copy + Default + Eq + Add<C, Output = C> + Ord,
+    FM: Fn(&Heading<S>, Turn, &T) -> Option<(S, C)>,
+    FG: Fn(&Heading<S>) -> bool,
+    FH: Fn(&Heading<S>) -> C,
+{
+    /// Sets the turns that are considered for each move, by default [Turn::FORWARD]
+    pub fn turns(mut self, turns: &[Turn]) -> Self {
+        self.turns = turns.to_vec();
+        self
+    }
+
+    pub fn goal<G>(self, goal: G) -> OrientedSearch<'a, T, S, C, FM, G, FH>
+    where
+        G: Fn(&Heading<S>) -> bool,
+    {
+        OrientedSearch {
+            grid: self.grid,
+            turns: self.turns,
+            move_cost: self.move_cost,
+            goal,
+            heuristic: self.heuristic,
+            _types: PhantomData,
+        }
+    }
+
+    /// Sets the heuristic for the A* search, which should never overestimate the remaining cost
+    pub fn heuristic<H>(self, heuristic: H) -> OrientedSearch<'a, T, S, C, FM, FG, H>
+    where
+        H: Fn(&Heading<S>) -> C,
+    {
+        OrientedSearch {
+            grid: self.grid,
+            turns: self.turns,
+            move_cost: self.move_cost,
+            goal: self.goal,
+            heuristic,
+            _types: PhantomData,
+        }
+    }
+
+    /// Finds the cheapest path from any of the start headings to a heading matching the goal
+    pub fn search(
+        &self,
+        starts: impl IntoIterator<Item = Heading<S>>,
+    ) -> Option<OrientedPath<S, C>> {
+        let (cost, mut path) = a_star_search_from(self, starts)?;
+        path.reverse();
+        Some(OrientedPath { cost, path })
+    }
+}
+
+impl<'a, T, S, C, FM, FG, FH> SearchGraph for OrientedSearch<'a, T, S, C, FM, FG, FH>
+where
+    S: Copy + Eq + Hash,
+    C: Copy + Default + Eq + Add<C, Output = C> + Ord,
+    FM: Fn(&Heading<S>, Turn, &T) -> Option<(S, C)>,
+{
+    type Node = Heading<S>;
+    type Score = C;
+
+    fn neighbours(&self, node: Heading<S>) -> impl Iterator<Item = (Heading<S>, C)> {
+        self.turns.iter().flat_map(move |&turn| {
+            let direction = turn.apply(node.direction);
+            let location = node.location + direction;
+            let value = self.grid.get(location)?;
+            let (state, cost) = (self.move_cost)(&node, turn, value)?;
+            Some((Heading { location, direction, state }, cost))
+        })
+    }
+
+    fn expected_state_size(&self) -> usize { self.grid.len() * 4 }
+}
+
+impl<'a, T, S, C, FM, FG, FH> SearchGraphWithGoal for OrientedSearch<'a, T, S, C, FM, FG, FH>
+where
+    S: Copy + Eq + Hash,
+    C: Copy + Default + Eq + Add<C, Output = C> + Ord,
+    FM: Fn(&Heading<S>, Turn, &T) -> Option<(S, C)>,
+    FG: Fn(&Heading<S>) -> bool,
+    FH: Fn(&Heading<S>) -> C,
+{
+    fn is_goal(&self, node: Heading<S>) -> bool { (self.goal)(&node) }
+
+    fn heuristic(&self, node: Heading<S>) -> C { (self.heuristic)(&node) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Heading, Turn};
+    use crate::direction::Direction::*;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    const CITY: &str = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n\
+                        4546657867536\n1438598798454\n4457876987766\n3637877979653\n\
+                        4654967986887\n4564679986453\n1224686865563\n2546548887735\n\
+                        4322674655533";
+
+    fn crucible(min_straight: u8, max_straight: u8) -> Option<u32> {
+        let grid: Result<Grid<char>, Error<_>> = Grid::parse_complete(CITY);
+        let grid = grid.unwrap().map(|&c| c as u32 - '0' as u32);
+        let end = point2(grid.width() - 1, grid.height() - 1);
+        let starts = [East, South].map(|dir| Heading::new(point2(0, 0), dir, 0u8));
+
+        let result = grid
+            .oriented_search(|heading, turn, &heat| match turn {
+                Turn::Straight if heading.state < max_straight => Some((heading.state + 1, heat)),
+                Turn::Left | Turn::Right if heading.state >= min_straight => Some((1, heat)),
+                _ => None,
+            })
+            .goal(|heading| heading.location == end && heading.state >= min_straight)
+            .heuristic(|heading| (end - heading.location).euler() as u32)
+            .search(starts)?;
+
+        assert_eq!(point2(0, 0), result.path.first()?.location);
+        assert_eq!(end, result.path.last()?.location);
+        Some(result.cost)
+    }
+
+    #[test]
+    fn normal_crucible() { assert_eq!(Some(102), crucible(0, 3)) }
+
+    #[test]
+    fn ultra_crucible() { assert_eq!(Some(94), crucible(4, 10)) }
+
+    #[test]
+    fn reverse_turns_are_opt_in() {
+        assert_eq!(Turn::Reverse.apply(North), South);
+        let grid = Grid::new_default('.', 3, 1);
+        let search = grid
+            .oriented_search(|_, _, _| Some(((), 1)))
+            .goal(|heading| heading.location == point2(0, 0));
+        let start = Heading::new(point2(1, 0), East, ());
+        assert_eq!(None, search.search([start]));
+        let result = search.turns(&Turn::ALL).search([start]).unwrap();
+        assert_eq!(1, result.cost);
+    }
+}
@@ -0,0 +1,247 @@
+use super::{Grid, Location};
+use crate::geometry::{Vector, point2};
+
+/// One of the 8 ways to rotate and mirror a grid, where rotations are clockwise and mirroring
+/// swaps the left and right side before rotating.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Mirror,
+    MirrorRotate90,
+    MirrorRotate180,
+    MirrorRotate270,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::Mirror,
+        Orientation::MirrorRotate90,
+        Orientation::MirrorRotate180,
+        Orientation::MirrorRotate270,
+    ];
+
+    pub const ROTATIONS: [Orientation; 4] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+    ];
+
+    const fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::MirrorRotate90
+                | Orientation::MirrorRotate270
+        )
+    }
+
+    /// Maps a location in a grid of the given width and height to its location after applying
+    /// this orientation.
+    fn map(self, location: Location, width: i32, height: i32) -> Location {
+        let (x, y) = (location.x(), location.y());
+        let (right, bottom) = (width - 1, height - 1);
+        match self {
+            Orientation::Identity => point2(x, y),
+            Orientation::Rotate90 => point2(bottom - y, x),
+            Orientation::Rotate180 => point2(right - x, bottom - y),
+            Orientation::Rotate270 => point2(y, right - x),
+            Orientation::Mirror => point2(right - x, y),
+            Orientation::MirrorRotate90 => point2(bottom - y, right - x),
+            Orientation::MirrorRotate180 => point2(x, bottom - y),
+            Orientation::MirrorRotate270 => point2(y, x),
+        }
+    }
+}
+
+/// A sequence found by [Grid::find_sequence]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SequenceMatch {
+    pub start: Location,
+    pub direction: Vector<2, i32>,
+    pub len: usize,
+}
+
+impl SequenceMatch {
+    /// All locations covered by the sequence, in order
+    pub fn locations(&self) -> impl Iterator<Item = Location> + use<> {
+        let (start, direction) = (self.start, self.direction);
+        (0..self.len as i32).map(move |step| start + direction * step)
+    }
+}
+
+/// A placement of a template found by [Grid::find_template]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TemplateMatch {
+    /// The top-left location of the (oriented) template in the grid
+    pub location: Location,
+    pub orientation: Orientation,
+    /// The locations in the grid that matched a non-wildcard cell of the template
+    pub cells: Vec<Location>,
+}
+
+impl<T> Grid<T> {
+    /// Returns a copy of this grid that has been rotated and/or mirrored
+    pub fn transform(&self, orientation: Orientation) -> Grid<T>
+    where
+        T: Clone + Default,
+    {
+        let (width, height) = if orientation.swaps_axes() {
+            (self.height(), self.width())
+        } else {
+            (self.width(), self.height())
+        };
+
+        let mut result = Grid::new_empty(width, height);
+        for (location, value) in self.entries() {
+            result[orientation.map(location, self.width(), self.height())] = value.clone();
+        }
+        result
+    }
+
+    /// Finds all places where the sequence can be read when starting at a location and stepping
+    /// in one of the given directions.
+    pub fn find_sequence<D>(
+        &self,
+        sequence: &[T],
+        directions: impl IntoIterator<Item = D>,
+    ) -> Vec<SequenceMatch>
+    where
+        T: PartialEq,
+        D: Into<Vector<2, i32>>,
+    {
+        let directions = directions.into_iter().map(D::into).collect::<Vec<_>>();
+        let Some(first) = sequence.first() else {
+            return Vec::new();
+        };
+
+        let mut result = Vec::new();
+        for start in self.locations_where(|value| value == first) {
+            for &direction in &directions {
+                let found = SequenceMatch { start, direction, len: sequence.len() };
+                if found
+                    .locations()
+                    .zip(sequence)
+                    .all(|(loc, expected)| self.get(loc) == Some(expected))
+                {
+                    result.push(found);
+                }
+            }
+        }
+        result
+    }
+
+    /// Finds all placements of the template in this grid, trying each of the given orientations.
+    /// Cells of the template that are `None` are wildcards and match anything. When different
+    /// orientations of a template are identical, only the first of those is reported.
+    pub fn find_template(
+        &self,
+        template: &Grid<Option<T>>,
+        orientations: &[Orientation],
+    ) -> Vec<TemplateMatch>
+    where
+        T: PartialEq + Clone,
+    {
+        let mut oriented: Vec<(Orientation, Grid<Option<T>>)> = Vec::new();
+        for &orientation in orientations {
+            let transformed = template.transform(orientation);
+            if !oriented.iter().any(|(_, existing)| *existing == transformed) {
+                oriented.push((orientation, transformed));
+            }
+        }
+
+        let mut result = Vec::new();
+        for (orientation, template) in &oriented {
+            let pattern = template
+                .entries()
+                .filter_map(|(offset, value)| value.as_ref().map(|value| (offset.coords, value)))
+                .collect::<Vec<_>>();
+
+            for y in 0..=(self.height() - template.height()) {
+                for x in 0..=(self.width() - template.width()) {
+                    let location = point2(x, y);
+                    if pattern
+                        .iter()
+                        .all(|&(offset, value)| self[location + Vector::from(offset)] == *value)
+                    {
+                        result.push(TemplateMatch {
+                            location,
+                            orientation: *orientation,
+                            cells: pattern
+                                .iter()
+                                .map(|&(offset, _)| location + Vector::from(offset))
+                                .collect(),
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Orientation;
+    use crate::direction::CardinalDirection;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    const WORD_SEARCH: &str = "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+                               XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX";
+
+    #[test]
+    fn find_words_in_all_directions() {
+        let grid = parse(WORD_SEARCH);
+        let found = grid.find_sequence(&['X', 'M', 'A', 'S'], CardinalDirection::ALL);
+        assert_eq!(18, found.len());
+        let first = found.iter().find(|m| m.start == point2(5, 0)).unwrap();
+        assert_eq!(
+            vec![point2(5, 0), point2(6, 0), point2(7, 0), point2(8, 0)],
+            first.locations().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn find_template_with_wildcards_and_rotations() {
+        let grid = parse(WORD_SEARCH);
+        let template = parse("M.S\n.A.\nM.S").map(|&c| if c == '.' { None } else { Some(c) });
+        let found = grid.find_template(&template, &Orientation::ALL);
+        assert_eq!(9, found.len());
+        assert!(found.iter().all(|m| m.cells.len() == 5));
+        assert_eq!(
+            2,
+            grid.find_template(&template, &[Orientation::Identity]).len()
+        );
+    }
+
+    #[test]
+    fn transform_grids() {
+        let grid = parse("ab\ncd\nef");
+        assert_eq!(parse("eca\nfdb"), grid.transform(Orientation::Rotate90));
+        assert_eq!(parse("fe\ndc\nba"), grid.transform(Orientation::Rotate180));
+        assert_eq!(parse("ba\ndc\nfe"), grid.transform(Orientation::Mirror));
+        assert_eq!(
+            parse("ace\nbdf"),
+            grid.transform(Orientation::MirrorRotate270)
+        );
+        for orientation in Orientation::ALL {
+            assert_eq!(6, grid.transform(orientation).len());
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use super::{Grid, Location};
+use crate::geometry::{Vector, vector2};
+use crate::math::greatest_common_divisor;
+
+/// Walks from a location in steps of a fixed vector, stopping at the edge of the grid or right
+/// after a blocking cell. The starting location itself is not included.
+pub struct Ray<'a, T, F> {
+    grid: &'a Grid<T>,
+    location: Location,
+    step: Vector<2, i32>,
+    is_blocking: F,
+    blocked: bool,
+}
+
+impl<'a, T, F> Iterator for Ray<'a, T, F>
+where
+    F: Fn(&T) -> bool,
+{
+    type Item = (Location, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.blocked || self.step == vector2(0, 0) {
+            return None;
+        }
+
+        self.location += self.step;
+        let value = self.grid.get(self.location)?;
+        self.blocked = (self.is_blocking)(value);
+        Some((self.location, value))
+    }
+}
+
+/// Divides a step by the greatest common divisor of its components, so the result is the
+/// smallest step that still passes through all the same grid cells. E.g. (4, -6) becomes (2, -3).
+pub fn reduced_step(step: Vector<2, i32>) -> Vector<2, i32> {
+    let (x, y) = (step.x().abs(), step.y().abs());
+    let divisor = match (x, y) {
+        (0, 0) => return step,
+        (0, d) | (d, 0) => d,
+        (x, y) => greatest_common_divisor(x, y),
+    };
+    vector2(step.x() / divisor, step.y() / divisor)
+}
+
+impl<T> Grid<T> {
+    /// All cells from the location (exclusive) in steps of `step`, until the edge of the grid
+    pub fn ray(
+        &self,
+        from: Location,
+        step: impl Into<Vector<2, i32>>,
+    ) -> Ray<'_, T, fn(&T) -> bool> {
+        self.ray_until(from, step, |_| false)
+    }
+
+    /// All cells from the location (exclusive) in steps of `step`, until the edge of the grid or
+    /// up to and including the first cell that is blocking.
+    pub fn ray_until<F>(
+        &self,
+        from: Location,
+        step: impl Into<Vector<2, i32>>,
+        is_blocking: F,
+    ) -> Ray<'_, T, F>
+    where
+        F: Fn(&T) -> bool,
+    {
+        Ray { grid: self, location: from, step: step.into(), is_blocking, blocked: false }
+    }
+
+    /// The first location in the direction of `step` where the cell is visible
+    pub fn first_visible<F>(
+        &self,
+        from: Location,
+        step: impl Into<Vector<2, i32>>,
+        is_visible: F,
+    ) -> Option<Location>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.ray_until(from, step, &is_visible)
+            .last()
+            .filter(|(_, value)| is_visible(value))
+            .map(|(location, _)| location)
+    }
+
+    /// All locations in row order with a visible cell that can be seen from the location, when
+    /// each visible cell blocks the line of sight to the cells behind it. This checks every
+    /// direction, not only the horizontal, vertical or diagonal ones.
+    pub fn visible_from<F>(&self, from: Location, is_visible: F) -> Vec<Location>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.locations_where(&is_visible)
+            .filter(|&location| location != from)
+            .filter(|&location| {
+                self.first_visible(from, reduced_step(location - from), &is_visible)
+                    == Some(location)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reduced_step;
+    use crate::direction::Direction;
+    use crate::geometry::{point2, vector2};
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn reduce_steps() {
+        assert_eq!(vector2(2, -3), reduced_step(vector2(4, -6)));
+        assert_eq!(vector2(0, -1), reduced_step(vector2(0, -5)));
+        assert_eq!(vector2(1, 0), reduced_step(vector2(7, 0)));
+        assert_eq!(vector2(0, 0), reduced_step(vector2(0, 0)));
+    }
+
+    #[test]
+    fn rays_stop_at_edge() {
+        let grid = parse("abc\ndef\nghi");
+        let ray = grid.ray(point2(0, 0), vector2(1, 1)).map(|(_, &c)| c);
+        assert_eq!("ei", ray.collect::<String>());
+        let ray = grid.ray(point2(0, 2), vector2(2, -1)).map(|(_, &c)| c);
+        assert_eq!("f", ray.collect::<String>());
+        assert_eq!(0, grid.ray(point2(1, 1), vector2(0, 0)).count());
+    }
+
+    #[test]
+    fn tree_viewing_distance() {
+        let trees = parse("30373\n25512\n65332\n33549\n35390");
+        let house = point2(2, 3);
+        let score: usize = Direction::ALL
+            .iter()
+            .map(|&dir| trees.ray_until(house, dir, |&tree| tree >= trees[house]).count())
+            .product();
+        assert_eq!(8, score);
+    }
+
+    #[test]
+    fn asteroid_line_of_sight() {
+        let asteroids = parse(".#..#\n.....\n#####\n....#\n...##");
+        let visible = |x, y| asteroids.visible_from(point2(x, y), |&c| c == '#').len();
+        assert_eq!(8, visible(3, 4));
+        assert_eq!(7, visible(1, 0));
+        assert_eq!(6, visible(0, 2));
+        assert_eq!(5, visible(4, 2));
+        assert_eq!(
+            Some(point2(1, 2)),
+            asteroids.first_visible(point2(1, 0), vector2(0, 1), |&c| c == '#')
+        );
+        assert_eq!(
+            None,
+            asteroids.first_visible(point2(0, 2), vector2(0, 1), |&c| c == '#')
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use super::layout::{self, GridLayout};
+use super::{Grid, Location};
+use crate::direction::CardinalDirection::*;
+use crate::direction::{CardinalDirection, Direction};
+use crate::geometry::{BoundingBox, FindBoundingBox, point2, vector2};
+
+/// A connected area of cells in a [Grid], as found by [Grid::detect_regions]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region<T> {
+    /// The value of the first cell of the region, in row order. Only neighbouring cells are
+    /// compared, so with [Grid::detect_regions_by] the other cells don't need to be equivalent
+    /// to this value, just to one of their neighbours in the region.
+    pub value: T,
+    /// All cells that are part of this region
+    pub cells: Vec<Location>,
+    /// The number of cell edges that border on something that is not part of this region
+    pub perimeter: usize,
+    /// The number of straight sides of the region, including the sides of any holes. Since each
+    /// side ends in a corner, this is also the number of corners.
+    pub sides: usize,
+    /// The number of enclosed areas inside this region that are not part of it
+    pub holes: usize,
+    /// The smallest box (inclusive) that contains all cells of this region
+    pub bounding_box: BoundingBox<2, i32>,
+    /// The indices of the other regions that share an edge with this region, sorted
+    pub adjacent: Vec<usize>,
+}
+
+impl<T> Region<T> {
+    pub fn area(&self) -> usize { self.cells.len() }
+}
+
+const CORNERS: [[CardinalDirection; 3]; 4] = [[N, NE, E], [E, SE, S], [S, SW, W], [W, NW, N]];
+
+impl<T> Grid<T> {
+    /// Finds all regions of directly connected cells with equal values
+    pub fn detect_regions(&self) -> Vec<Region<T>>
+    where
+        T: Eq + Clone,
+    {
+        self.detect_regions_by(|a, b| a == b)
+    }
+
+    /// Finds all regions of directly connected cells, where two neighbouring cells are part of the
+    /// same region if the `equivalent` function returns true for their values.
+    pub fn detect_regions_by<F>(&self, equivalent: F) -> Vec<Region<T>>
+    where
+        T: Clone,
+        F: Fn(&T, &T) -> bool,
+    {
+        detect_regions_by(self, equivalent)
+    }
+}
+
+/// The extra storage that is needed to find regions, so the same algorithm can be used for each
+/// [GridLayout] of a grid.
+pub(super) trait RegionCells: GridLayout<Coord = i32> {
+    type Ids: RegionCells<Cell = usize>;
+
+    /// Creates storage of the same layout and size, where each cell is `usize::MAX`
+    fn new_ids(&self) -> Self::Ids;
+}
+
+impl<T> RegionCells for Grid<T> {
+    type Ids = Grid<usize>;
+
+    fn new_ids(&self) -> Self::Ids { self.map(|_| usize::MAX) }
+}
+
+pub(super) fn detect_regions_by<T, G, F>(cells: &G, equivalent: F) -> Vec<Region<T>>
+where
+    T: Clone,
+    G: RegionCells<Cell = T>,
+    F: Fn(&T, &T) -> bool,
+{
+    let region_ids = region_ids(cells, &equivalent);
+    let mut regions = Vec::<Region<T>>::new();
+
+    for location in layout::row_order(cells.layout_size()) {
+        let id = region_ids[location];
+        if id == regions.len() {
+            regions.push(Region {
+                value: cells[location].clone(),
+                cells: Vec::new(),
+                perimeter: 0,
+                sides: 0,
+                holes: 0,
+                bounding_box: BoundingBox::from(location, location),
+                adjacent: Vec::new(),
+            });
+        }
+        let region = &mut regions[id];
+        region.cells.push(location);
+
+        for dir in Direction::ALL {
+            match region_ids.cell(location + dir) {
+                Some(&other) if other == id => {}
+                Some(&other) => {
+                    region.perimeter += 1;
+                    if !region.adjacent.contains(&other) {
+                        region.adjacent.push(other);
+                    }
+                }
+                None => region.perimeter += 1,
+            }
+        }
+
+        region.sides += CORNERS
+            .iter()
+            .filter(|dirs| {
+                let [forward, diagonal, side] =
+                    dirs.map(|dir| region_ids.cell(location + dir) == Some(&id));
+                (forward && !diagonal && side) || (!forward && !side)
+            })
+            .count();
+    }
+
+    for region in regions.iter_mut() {
+        region.adjacent.sort();
+        region.bounding_box = region.cells.iter().copied().enclosing_rect().unwrap();
+        region.holes = count_holes(&region.cells, region.bounding_box);
+    }
+
+    regions
+}
+
+/// Returns the storage where each cell contains the index of the region it belongs to. Regions
+/// are numbered in the order in which their first cell appears in the grid.
+fn region_ids<T, G, F>(cells: &G, equivalent: F) -> G::Ids
+where
+    G: RegionCells<Cell = T>,
+    F: Fn(&T, &T) -> bool,
+{
+    let mut region_ids = cells.new_ids();
+    let mut next_id = 0;
+    let mut stack = Vec::new();
+
+    for start in layout::row_order(cells.layout_size()) {
+        if region_ids[start] != usize::MAX {
+            continue;
+        }
+
+        region_ids[start] = next_id;
+        stack.push(start);
+        while let Some(location) = stack.pop() {
+            let current = &cells[location];
+            for dir in Direction::ALL {
+                let next = location + dir;
+                if let Some(neighbour) = cells.cell(next)
+                    && region_ids[next] == usize::MAX
+                    && equivalent(current, neighbour)
+                {
+                    region_ids[next] = next_id;
+                    stack.push(next);
+                }
+            }
+        }
+        next_id += 1;
+    }
+
+    region_ids
+}
+
+/// Counts the enclosed areas that are not part of the region. The outside cells are considered
+/// connected diagonally, since the region itself is only connected directly.
+fn count_holes(cells: &[Location], bounding_box: BoundingBox<2, i32>) -> usize {
+    let offset = bounding_box.min_point() - point2(1, 1);
+    let size = bounding_box.total_size() + vector2(3, 3);
+    let mut outside = Grid::new_default(true, size.x(), size.y());
+    for &cell in cells {
+        outside[cell - offset] = false;
+    }
+
+    let mut components = 0;
+    let locations = outside.locations().collect::<Vec<_>>();
+    for start in locations {
+        if !outside[start] {
+            continue;
+        }
+
+        components += 1;
+        let mut stack = vec![start];
+        while let Some(location) = stack.pop() {
+            if let Some(cell) = outside.get_mut(location)
+                && *cell
+            {
+                *cell = false;
+                stack.extend(location.cardinal_neighbours());
+            }
+        }
+    }
+
+    // The first component is always the area around the region
+    components - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn region_statistics() {
+        let grid = parse("AAAA\nBBCD\nBBCC\nEEEC");
+        let regions = grid.detect_regions();
+        assert_eq!(5, regions.len());
+
+        let a = &regions[0];
+        assert_eq!(('A', 4, 10, 4), (a.value, a.area(), a.perimeter, a.sides));
+        assert_eq!(vec![1, 2, 3], a.adjacent);
+
+        let c = &regions[2];
+        assert_eq!(('C', 4, 10, 8), (c.value, c.area(), c.perimeter, c.sides));
+        assert_eq!(vec![0, 1, 3, 4], c.adjacent);
+    }
+
+    #[test]
+    fn regions_with_holes() {
+        let grid = parse("OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO");
+        let regions = grid.detect_regions();
+        assert_eq!(5, regions.len());
+        let o = &regions[0];
+        assert_eq!((21, 36, 20, 4), (o.area(), o.perimeter, o.sides, o.holes));
+        assert_eq!(0, regions[1].holes);
+    }
+
+    #[test]
+    fn regions_with_custom_predicate() {
+        let grid = parse("1234\n5678\n9999").map(|c| *c as u8 - b'0');
+        let regions = grid.detect_regions_by(|a, b| a.abs_diff(*b) <= 1);
+        assert_eq!(2, regions.len());
+        assert_eq!(4, regions[0].area());
+        assert_eq!(8, regions[1].area());
+    }
+}
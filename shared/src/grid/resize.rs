@@ -0,0 +1,156 @@
+use super::{Grid, Location, Size};
+use crate::geometry::{point2, vector2};
+use std::ops::Range;
+
+impl<T> Grid<T> {
+    /// Returns a copy of this grid with a border of `amount` cells with the given value around it
+    pub fn padded(&self, amount: i32, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut result = self.clone();
+        result.grow(vector2(amount, amount), vector2(amount, amount), value);
+        result
+    }
+
+    /// Grows this grid in place. `before` is the number of columns and rows added on the left and
+    /// top, `after` the number added on the right and bottom. Existing cells move by `before`.
+    pub fn grow(&mut self, before: Size, after: Size, value: T)
+    where
+        T: Clone,
+    {
+        assert!(
+            before.x() >= 0 && before.y() >= 0 && after.x() >= 0 && after.y() >= 0,
+            "Can only grow a grid, use sub_grid to shrink it"
+        );
+        let size = self.size + before + after;
+        let mut items = Vec::with_capacity((size.x() * size.y()) as usize);
+        let mut old_items = std::mem::take(&mut self.items).into_iter();
+        items.resize(
+            items.len() + (before.y() * size.x()) as usize,
+            value.clone(),
+        );
+        for _ in 0..self.height() {
+            items.resize(items.len() + before.x() as usize, value.clone());
+            items.extend(old_items.by_ref().take(self.width() as usize));
+            items.resize(items.len() + after.x() as usize, value.clone());
+        }
+        items.resize((size.x() * size.y()) as usize, value);
+
+        self.items = items;
+        self.size = size;
+    }
+
+    /// Grows this grid in place just enough to include the location. Returns how much all
+    /// existing locations have moved, which needs to be added to the location to find it in
+    /// the grown grid.
+    pub fn grow_to_include(&mut self, location: Location, value: T) -> Size
+    where
+        T: Clone,
+    {
+        let before = vector2((-location.x()).max(0), (-location.y()).max(0));
+        let after = vector2(
+            (location.x() - self.width() + 1).max(0),
+            (location.y() - self.height() + 1).max(0),
+        );
+        if before != vector2(0, 0) || after != vector2(0, 0) {
+            self.grow(before, after, value);
+        }
+        before
+    }
+
+    /// Builds a grid of `columns` by `rows` copies of this grid. Each value is transformed
+    /// using the column and row of the tile it ends up in.
+    pub fn tiled<F>(&self, columns: i32, rows: i32, transform: F) -> Grid<T>
+    where
+        F: Fn(&T, i32, i32) -> T,
+    {
+        let size = vector2(self.width() * columns, self.height() * rows);
+        let mut items = Vec::with_capacity((size.x() * size.y()) as usize);
+        for tile_y in 0..rows {
+            for row in self.items.chunks(self.width().max(1) as usize) {
+                for tile_x in 0..columns {
+                    items.extend(row.iter().map(|value| transform(value, tile_x, tile_y)));
+                }
+            }
+        }
+        Grid { items, size }
+    }
+
+    /// The smallest ranges of columns and rows that contain all cells that are not empty, or
+    /// `None` if all cells are empty.
+    pub fn trim_bounds<F>(&self, is_empty: F) -> Option<(Range<i32>, Range<i32>)>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut locations = self.locations_where(|value| !is_empty(value));
+        let first = locations.next()?;
+        let (mut min, mut max) = (first, first);
+        for location in locations {
+            min = point2(min.x().min(location.x()), min.y().min(location.y()));
+            max = point2(max.x().max(location.x()), max.y().max(location.y()));
+        }
+        Some((min.x()..max.x() + 1, min.y()..max.y() + 1))
+    }
+
+    /// Returns a copy of this grid without the rows and columns on the outside that only
+    /// contain default values.
+    pub fn trimmed(&self) -> Grid<T>
+    where
+        T: Default + PartialEq + Clone,
+    {
+        let empty = T::default();
+        match self.trim_bounds(|value| *value == empty) {
+            Some((x_range, y_range)) => self.sub_grid(x_range, y_range),
+            None => Grid::new_empty(0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{point2, vector2};
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn pad_and_grow() {
+        let grid = parse("ab\ncd");
+        assert_eq!(parse("....\n.ab.\n.cd.\n...."), grid.padded(1, '.'));
+
+        let mut grown = grid.clone();
+        grown.grow(vector2(0, 1), vector2(2, 0), '.');
+        assert_eq!(parse("....\nab..\ncd.."), grown);
+
+        let mut grown = grid.clone();
+        assert_eq!(vector2(1, 0), grown.grow_to_include(point2(-1, 2), '.'));
+        assert_eq!(parse(".ab\n.cd\n..."), grown);
+        assert_eq!(vector2(0, 0), grown.grow_to_include(point2(1, 1), '.'));
+        assert_eq!(9, grown.len());
+    }
+
+    #[test]
+    fn tile_risk_map() {
+        let grid = Grid::new_default(8u8, 2, 1);
+        let tiled = grid.tiled(3, 2, |&risk, x, y| (risk + (x + y) as u8 - 1) % 9 + 1);
+        assert_eq!(vector2(6, 2), vector2(tiled.width(), tiled.height()));
+        let values = tiled.values().copied().collect::<Vec<_>>();
+        assert_eq!(vec![8, 8, 9, 9, 1, 1, 9, 9, 1, 1, 2, 2], values);
+    }
+
+    #[test]
+    fn trim_to_content() {
+        let grid = parse("......\n..#...\n....#.\n......").map(|&c| c == '#');
+        let trimmed = grid.trimmed();
+        assert_eq!(vector2(3, 2), vector2(trimmed.width(), trimmed.height()));
+        assert!(trimmed[point2(0, 0)] && trimmed[point2(2, 1)]);
+        assert_eq!(Some((2..5, 1..3)), grid.trim_bounds(|&filled| !filled));
+        assert!(Grid::new_default(false, 3, 3).trimmed().is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Grid, Location};
+use crate::direction::Direction;
+use crate::geometry::Vector;
+use fxhash::FxHashSet;
+use std::collections::VecDeque;
+
+impl<T> Grid<T> {
+    /// Moves every movable cell as far as possible in the given direction, until it hits the edge
+    /// or a cell that is neither empty nor movable. Returns the number of cells that moved.
+    pub fn slide<M, E>(&mut self, direction: Direction, is_movable: M, is_empty: E) -> usize
+    where
+        M: Fn(&T) -> bool,
+        E: Fn(&T) -> bool,
+    {
+        // Each line starts at the edge the cells slide towards
+        let lines: Vec<Vec<Location>> = match direction {
+            Direction::North => self.south_lines(),
+            Direction::East => self.west_lines(),
+            Direction::South => self.north_lines(),
+            Direction::West => self.east_lines(),
+        }
+        .map(|line| line.map(|(location, _)| location).collect())
+        .collect();
+
+        let mut moved = 0;
+        for line in lines {
+            let mut free = 0;
+            for (ix, &location) in line.iter().enumerate() {
+                let cell = &self[location];
+                if is_movable(cell) {
+                    if free != ix {
+                        self.swap(line[free], location);
+                        moved += 1;
+                    }
+                    free += 1;
+                } else if !is_empty(cell) {
+                    free = ix + 1;
+                }
+            }
+        }
+        moved
+    }
+
+    /// Pushes the cell at `start` one step in the given direction, together with all movable
+    /// cells in front of it. Returns false (and changes nothing) if that is not possible because
+    /// a cell that is not movable or the edge of the grid is in the way.
+    pub fn push<M, E>(
+        &mut self,
+        start: Location,
+        direction: Direction,
+        is_movable: M,
+        is_empty: E,
+    ) -> bool
+    where
+        M: Fn(&T) -> bool,
+        E: Fn(&T) -> bool,
+    {
+        self.push_linked(start, direction, is_movable, is_empty, |_, _| None)
+    }
+
+    /// The same as [Grid::push], but objects can span multiple cells. The `linked` function
+    /// returns the other locations that belong to the same object as the given cell, which are
+    /// then pushed as well.
+    pub fn push_linked<M, E, L, I>(
+        &mut self,
+        start: Location,
+        direction: Direction,
+        is_movable: M,
+        is_empty: E,
+        linked: L,
+    ) -> bool
+    where
+        M: Fn(&T) -> bool,
+        E: Fn(&T) -> bool,
+        L: Fn(Location, &T) -> I,
+        I: IntoIterator<Item = Location>,
+    {
+        let mut moving = Vec::new();
+        let mut seen = FxHashSet::default();
+        let mut queue = VecDeque::from([start]);
+        while let Some(location) = queue.pop_front() {
+            if !seen.insert(location) {
+                continue;
+            }
+            let Some(cell) = self.get(location) else {
+                return false; // Pushing over the edge
+            };
+            if location != start && is_empty(cell) {
+                continue;
+            }
+            if !is_movable(cell) {
+                return false;
+            }
+            moving.push(location);
+            queue.push_back(location + direction);
+            queue.extend(linked(location, cell));
+        }
+
+        // Move the cells furthest ahead first, so each one moves into an empty spot
+        let step = Vector::<2, i32>::from(direction);
+        moving.sort_by_key(|location| -(location.x() * step.x() + location.y() * step.y()));
+        for location in moving {
+            self.swap(location, location + direction);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction::Direction;
+    use crate::direction::Direction::*;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn parse(input: &str) -> Grid<char> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap()
+    }
+
+    #[test]
+    fn tilt_rocks() {
+        let mut grid = parse(
+            "O....#....\nO.OO#....#\n.....##...\nOO.#O....O\n.O.....O#.\n\
+             O.#..O.#.#\n..O..#O..O\n.......O..\n#....###..\n#OO..#....",
+        );
+        grid.slide(North, |&c| c == 'O', |&c| c == '.');
+        let load: i32 = grid
+            .locations_where(|&c| c == 'O')
+            .map(|location| grid.height() - location.y())
+            .sum();
+        assert_eq!(136, load);
+
+        let mut line = parse("O.#.OO.");
+        assert_eq!(3, line.slide(East, |&c| c == 'O', |&c| c == '.'));
+        assert_eq!(parse(".O#..OO"), line);
+        assert_eq!(0, line.slide(East, |&c| c == 'O', |&c| c == '.'));
+    }
+
+    #[test]
+    fn push_chain_of_boxes() {
+        let mut grid = parse("#@OO.O#");
+        let is_movable = |&c: &char| c == '@' || c == 'O';
+        assert!(grid.push(point2(1, 0), East, is_movable, |&c| c == '.'));
+        assert_eq!(parse("#.@OOO#"), grid);
+        assert!(!grid.push(point2(2, 0), East, is_movable, |&c| c == '.'));
+        assert_eq!(parse("#.@OOO#"), grid);
+    }
+
+    #[test]
+    fn push_wide_boxes() {
+        let mut grid = parse(
+            "##############\n##......##..##\n##..........##\n##....[][]@.##\n\
+             ##....[]....##\n##..........##\n##############",
+        );
+        let mut robot = grid.find(|&c| c == '@').unwrap();
+        for c in "<vv<<^^<<^^".bytes() {
+            let direction = Direction::from(c);
+            if grid.push_linked(
+                robot,
+                direction,
+                |&c| "@[]".contains(c),
+                |&c| c == '.',
+                |location, &c| match c {
+                    '[' => Some(location + East),
+                    ']' => Some(location + West),
+                    _ => None,
+                },
+            ) {
+                robot += direction;
+            }
+        }
+
+        let gps: i32 = grid.locations_where(|&c| c == '[').map(|l| l.y() * 100 + l.x()).sum();
+        assert_eq!(618, gps);
+        assert_eq!(point2(5, 2), robot);
+    }
+}
@@ -0,0 +1,223 @@
+use super::layout::{self, GridLayout, LinesIterator};
+use super::regions::{RegionCells, detect_regions_by};
+use super::{Grid, Location, Region, Size};
+use crate::direction::Direction;
+use std::ops::{Index, IndexMut};
+
+const TILE_BITS: i32 = 3;
+const TILE_SIZE: i32 = 1 << TILE_BITS;
+const TILE_MASK: i32 = TILE_SIZE - 1;
+
+/// A grid that stores its cells in square tiles of 8 by 8 cells instead of row by row. Cells that
+/// are close to each other, also vertically, are then close in memory, which makes going through
+/// columns and scanning neighbourhoods of large grids a lot friendlier for the cache. It offers the
+/// same methods as [Grid] for accessing cells, which share their implementation through
+/// [GridLayout], and converts from and to one.
+///
+/// The tiles on the right and bottom edge are padded with copies of the first cell, which can't
+/// be reached through the API.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TiledGrid<T> {
+    items: Vec<T>,
+    size: Size,
+    tiles_per_row: i32,
+}
+
+impl<T> TiledGrid<T> {
+    pub fn new_default(value: T, width: i32, height: i32) -> TiledGrid<T>
+    where
+        T: Clone,
+    {
+        TiledGrid::from(&Grid::new_default(value, width, height))
+    }
+
+    pub fn height(&self) -> i32 { self.size.y() }
+
+    pub fn width(&self) -> i32 { self.size.x() }
+
+    pub fn size(&self) -> Size { self.size }
+
+    pub fn len(&self) -> usize { (self.size.x() * self.size.y()) as usize }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn is_valid_location(&self, location: &Location) -> bool {
+        self.contains_location(location)
+    }
+
+    #[inline]
+    fn index_of(&self, location: Location) -> usize {
+        let (x, y) = (location.x(), location.y());
+        let tile = (y >> TILE_BITS) * self.tiles_per_row + (x >> TILE_BITS);
+        ((tile << (2 * TILE_BITS)) | ((y & TILE_MASK) << TILE_BITS) | (x & TILE_MASK)) as usize
+    }
+
+    pub fn get(&self, location: Location) -> Option<&T> { self.cell(location) }
+
+    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> { self.cell_mut(location) }
+
+    pub fn direct_neighbours(&self, location: Location) -> impl Iterator<Item = (Direction, &T)> {
+        layout::direct_neighbours(self, location)
+    }
+
+    /// All locations in row order, like [Grid::locations]
+    pub fn locations(&self) -> impl Iterator<Item = Location> + use<T> {
+        layout::row_order(self.size)
+    }
+
+    /// All cells with their location in row order, like [Grid::entries]
+    pub fn entries(&self) -> impl Iterator<Item = (Location, &T)> { layout::entries(self) }
+
+    /// All columns, each going from the bottom to the top, like [Grid::north_lines]
+    pub fn north_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::north(self) }
+
+    /// All rows, each going from the left to the right, like [Grid::east_lines]
+    pub fn east_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::east(self) }
+
+    /// All columns, each going from the top to the bottom, like [Grid::south_lines]
+    pub fn south_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::south(self) }
+
+    /// All rows, each going from the right to the left, like [Grid::west_lines]
+    pub fn west_lines(&self) -> LinesIterator<'_, Self> { LinesIterator::west(self) }
+
+    /// Flood fills the area of equal cells around the start with the value, like [Grid::fill]
+    pub fn fill(&mut self, start: Location, value: T) -> usize
+    where
+        T: PartialEq + Clone,
+    {
+        layout::fill(self, start, value)
+    }
+
+    /// Finds all regions of directly connected cells with equal values, like
+    /// [Grid::detect_regions]
+    pub fn detect_regions(&self) -> Vec<Region<T>>
+    where
+        T: Eq + Clone,
+    {
+        self.detect_regions_by(|a, b| a == b)
+    }
+
+    pub fn detect_regions_by<F>(&self, equivalent: F) -> Vec<Region<T>>
+    where
+        T: Clone,
+        F: Fn(&T, &T) -> bool,
+    {
+        detect_regions_by(self, equivalent)
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let items = self.entries().map(|(_, value)| value.clone()).collect();
+        Grid { items, size: self.size }
+    }
+}
+
+impl<T: Clone> From<&Grid<T>> for TiledGrid<T> {
+    fn from(grid: &Grid<T>) -> Self {
+        let tiles_per_row = (grid.width() + TILE_MASK) >> TILE_BITS;
+        let tiles_per_column = (grid.height() + TILE_MASK) >> TILE_BITS;
+        let mut result = TiledGrid { items: Vec::new(), size: grid.size(), tiles_per_row };
+        if let Some(padding) = grid.items.first() {
+            let len = ((tiles_per_row * tiles_per_column) << (2 * TILE_BITS)) as usize;
+            result.items.resize(len, padding.clone());
+        }
+        for (location, value) in grid.entries() {
+            let ix = result.index_of(location);
+            result.items[ix] = value.clone();
+        }
+        result
+    }
+}
+
+impl<T> Index<Location> for TiledGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: Location) -> &Self::Output { self.get(index).unwrap() }
+}
+
+impl<T> IndexMut<Location> for TiledGrid<T> {
+    fn index_mut(&mut self, index: Location) -> &mut Self::Output { self.get_mut(index).unwrap() }
+}
+
+impl<T> GridLayout for TiledGrid<T> {
+    type Cell = T;
+    type Coord = i32;
+
+    fn layout_size(&self) -> Size { self.size }
+
+    unsafe fn cell_unchecked(&self, location: Location) -> &T {
+        unsafe { self.items.get_unchecked(self.index_of(location)) }
+    }
+
+    unsafe fn cell_unchecked_mut(&mut self, location: Location) -> &mut T {
+        let ix = self.index_of(location);
+        unsafe { self.items.get_unchecked_mut(ix) }
+    }
+}
+
+impl<T> RegionCells for TiledGrid<T> {
+    type Ids = TiledGrid<usize>;
+
+    fn new_ids(&self) -> Self::Ids {
+        TiledGrid {
+            items: vec![usize::MAX; self.items.len()],
+            size: self.size,
+            tiles_per_row: self.tiles_per_row,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TiledGrid;
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+
+    fn test_grid() -> Grid<u8> {
+        let mut grid = Grid::new_default(0u8, 19, 11);
+        for (location, value) in grid.entries_mut() {
+            *value =
+                ((location.x() / 3 + location.y() / 2 + location.x() * location.y() % 4) % 3) as u8;
+        }
+        grid
+    }
+
+    #[test]
+    fn same_cells_as_grid() {
+        let grid = test_grid();
+        let tiled = TiledGrid::from(&grid);
+        assert_eq!(grid.size(), tiled.size());
+        assert!(grid.locations().all(|location| grid.get(location) == tiled.get(location)));
+        assert_eq!(None, tiled.get(point2(19, 0)));
+        assert_eq!(None, tiled.get(point2(0, -1)));
+        assert_eq!(grid, tiled.to_grid());
+        assert!(TiledGrid::new_default('.', 0, 0).is_empty());
+    }
+
+    #[test]
+    fn lines_like_grid() {
+        let grid = test_grid();
+        let tiled = TiledGrid::from(&grid);
+        let north = grid.north_lines().flatten().collect::<Vec<_>>();
+        assert_eq!(north, tiled.north_lines().flatten().collect::<Vec<_>>());
+        let south = grid.south_lines().flatten().collect::<Vec<_>>();
+        assert_eq!(south, tiled.south_lines().flatten().collect::<Vec<_>>());
+        let east = grid.east_lines().flatten().collect::<Vec<_>>();
+        assert_eq!(east, tiled.east_lines().flatten().collect::<Vec<_>>());
+        let west = grid.west_lines().flatten().collect::<Vec<_>>();
+        assert_eq!(west, tiled.west_lines().flatten().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fill_and_regions_like_grid() {
+        let mut grid = test_grid();
+        let mut tiled = TiledGrid::from(&grid);
+        assert_eq!(grid.detect_regions(), tiled.detect_regions());
+
+        let start = point2(10, 5);
+        assert_eq!(grid.fill(start, 9), tiled.fill(start, 9));
+        assert_eq!(grid, tiled.to_grid());
+    }
+}
@@ -0,0 +1,206 @@
+use super::{Grid, Location};
+use crate::geometry::{point2, vector2};
+use std::collections::VecDeque;
+use std::ops::{Add, Range, Sub};
+
+/// Prefix sums of a grid, which can give the sum of any rectangle in constant time. Each cell at
+/// (x, y) contains the sum of all values above and to the left of it (exclusive), so the table is
+/// one larger than the grid in both directions.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SummedAreaTable<N> {
+    sums: Grid<N>,
+}
+
+impl<N> SummedAreaTable<N>
+where
+    N: Copy + Default + Add<Output = N> + Sub<Output = N>,
+{
+    pub fn width(&self) -> i32 { self.sums.width() - 1 }
+
+    pub fn height(&self) -> i32 { self.sums.height() - 1 }
+
+    /// The sum of all cells in the ranges of columns and rows, which are clamped to the grid
+    pub fn sum(&self, x_range: Range<i32>, y_range: Range<i32>) -> N {
+        let (min_x, max_x) = (x_range.start.max(0), x_range.end.min(self.width()));
+        let (min_y, max_y) = (y_range.start.max(0), y_range.end.min(self.height()));
+        if min_x >= max_x || min_y >= max_y {
+            return N::default();
+        }
+
+        self.sums[point2(max_x, max_y)] + self.sums[point2(min_x, min_y)]
+            - self.sums[point2(min_x, max_y)]
+            - self.sums[point2(max_x, min_y)]
+    }
+
+    /// The sum of the window of the given size that has its top-left corner at the location
+    pub fn window_sum(&self, top_left: Location, width: i32, height: i32) -> N {
+        self.sum(
+            top_left.x()..top_left.x() + width,
+            top_left.y()..top_left.y() + height,
+        )
+    }
+
+    /// The sums of all windows of the given size that fit in the grid, stored at the location of
+    /// their top-left corner.
+    pub fn window_sums(&self, width: i32, height: i32) -> Grid<N> {
+        let mut result = Grid::new_default(
+            N::default(),
+            (self.width() - width + 1).max(0),
+            (self.height() - height + 1).max(0),
+        );
+        for (location, sum) in result.entries_mut() {
+            *sum = self.window_sum(location, width, height);
+        }
+        result
+    }
+}
+
+/// Keeps the extreme value of each window of `size` values in a line, using a queue of the
+/// indices of values that could still become the extreme value of a later window.
+fn sliding_extremes<T, F>(line: &[T], size: usize, is_better: &F) -> Vec<T>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> bool,
+{
+    let mut queue = VecDeque::<usize>::with_capacity(size);
+    let mut result = Vec::with_capacity(line.len().saturating_sub(size) + 1);
+    for (ix, value) in line.iter().enumerate() {
+        while queue.back().is_some_and(|&last| !is_better(&line[last], value)) {
+            queue.pop_back();
+        }
+        queue.push_back(ix);
+        if queue[0] + size <= ix {
+            queue.pop_front();
+        }
+        if ix + 1 >= size {
+            result.push(line[queue[0]].clone());
+        }
+    }
+    result
+}
+
+impl<T> Grid<T> {
+    /// Builds a [SummedAreaTable] of the values that are mapped from each cell
+    pub fn summed_area_table<N, F>(&self, value: F) -> SummedAreaTable<N>
+    where
+        N: Copy + Default + Add<Output = N> + Sub<Output = N>,
+        F: Fn(&T) -> N,
+    {
+        let mut sums = Grid::new_default(N::default(), self.width() + 1, self.height() + 1);
+        for (location, cell) in self.entries() {
+            let below_right = location + vector2(1, 1);
+            sums[below_right] = value(cell)
+                + sums[point2(location.x(), below_right.y())]
+                + sums[point2(below_right.x(), location.y())]
+                - sums[location];
+        }
+        SummedAreaTable { sums }
+    }
+
+    fn window_extremes<F>(&self, width: i32, height: i32, is_better: F) -> Grid<T>
+    where
+        T: Clone,
+        F: Fn(&T, &T) -> bool,
+    {
+        assert!(
+            width > 0 && height > 0,
+            "Windows need to be at least 1 by 1"
+        );
+        if width > self.width() || height > self.height() {
+            return Grid { items: Vec::new(), size: vector2(0, 0) };
+        }
+
+        // First take the extremes in each row, then in each column of those results
+        let result_width = self.width() - width + 1;
+        let rows = self
+            .items
+            .chunks(self.width() as usize)
+            .flat_map(|row| sliding_extremes(row, width as usize, &is_better))
+            .collect::<Vec<_>>();
+        let rows = Grid { items: rows, size: vector2(result_width, self.height()) };
+
+        let mut items = Vec::with_capacity((result_width * (self.height() - height + 1)) as usize);
+        let columns = rows
+            .x_range()
+            .map(|x| {
+                let column = rows.south_line(x).map(|(_, v)| v.clone()).collect::<Vec<_>>();
+                sliding_extremes(&column, height as usize, &is_better)
+            })
+            .collect::<Vec<_>>();
+        for y in 0..(self.height() - height + 1) as usize {
+            items.extend(columns.iter().map(|column| column[y].clone()));
+        }
+        Grid { items, size: vector2(result_width, self.height() - height + 1) }
+    }
+
+    /// The largest value of each window of the given size that fits in the grid, stored at the
+    /// location of its top-left corner.
+    pub fn window_max(&self, width: i32, height: i32) -> Grid<T>
+    where
+        T: Ord + Clone,
+    {
+        self.window_extremes(width, height, |current, new| current > new)
+    }
+
+    /// The smallest value of each window of the given size that fits in the grid, stored at the
+    /// location of its top-left corner.
+    pub fn window_min(&self, width: i32, height: i32) -> Grid<T>
+    where
+        T: Ord + Clone,
+    {
+        self.window_extremes(width, height, |current, new| current < new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::point2;
+    use crate::grid::Grid;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    fn digits(input: &str) -> Grid<u8> {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete(input);
+        result.unwrap().map(|&c| c as u8 - b'0')
+    }
+
+    #[test]
+    fn rectangle_sums() {
+        let grid = digits("123\n456\n789");
+        let table = grid.summed_area_table(|&v| v as i32);
+        assert_eq!(45, table.sum(0..3, 0..3));
+        assert_eq!(5 + 6 + 8 + 9, table.sum(1..3, 1..3));
+        assert_eq!(4, table.window_sum(point2(0, 1), 1, 1));
+        assert_eq!(12, table.sum(-5..1, 0..10));
+        assert_eq!(0, table.sum(2..2, 0..3));
+        assert_eq!(
+            vec![12, 16, 24, 28],
+            table.window_sums(2, 2).values().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fuel_cell_power() {
+        let serial = 18;
+        let mut grid = Grid::new_default(0, 300, 300);
+        for (location, power) in grid.entries_mut() {
+            let rack = location.x() + 1 + 10;
+            *power = ((rack * (location.y() + 1) + serial) * rack / 100) % 10 - 5;
+        }
+
+        let sums = grid.summed_area_table(|&power| power).window_sums(3, 3);
+        let best = sums.locations().max_by_key(|&location| sums[location]).unwrap();
+        assert_eq!(point2(32, 44), best);
+        assert_eq!(29, sums[best]);
+    }
+
+    #[test]
+    fn window_max_and_min() {
+        let grid = digits("1523\n4867\n9012");
+        assert_eq!(digits("887\n987"), grid.window_max(2, 2));
+        assert_eq!(digits("122\n001"), grid.window_min(2, 2));
+        assert_eq!(digits("9867"), grid.window_max(1, 3));
+        assert_eq!(grid, grid.window_min(1, 1));
+        assert!(grid.window_max(5, 1).is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::geometry::{BoundingBox, Point, Vector};
+use crate::grid::Grid;
+use std::ops::{Index, IndexMut};
+
+/// Determines which cells are considered to be neighbours in a [GridN].
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Connectivity {
+    /// Only cells that share a face, so only a single coordinate differs
+    Face,
+    /// Cells that share at least an edge, so up to two coordinates differ
+    Edge,
+    /// All cells that touch, including the ones that only share a corner
+    Corner,
+}
+
+impl Connectivity {
+    fn max_changed_coords(self) -> usize {
+        match self {
+            Connectivity::Face => 1,
+            Connectivity::Edge => 2,
+            Connectivity::Corner => usize::MAX,
+        }
+    }
+
+    /// All unit steps that lead to a neighbour with this connectivity
+    pub fn offsets<const D: usize>(self) -> Vec<Vector<D, i32>> {
+        let max_changed = self.max_changed_coords();
+        let mut result = Vec::new();
+        for combination in 0..3usize.pow(D as u32) {
+            let mut coords = [0; D];
+            let mut rest = combination;
+            for coord in coords.iter_mut() {
+                *coord = (rest % 3) as i32 - 1;
+                rest /= 3;
+            }
+            let changed = coords.iter().filter(|&&c| c != 0).count();
+            if changed > 0 && changed <= max_changed {
+                result.push(Vector { coords });
+            }
+        }
+        result
+    }
+}
+
+/// A dense grid in `D` dimensions, storing a value for every point inside its bounding box.
+/// This is the voxel counterpart of [Grid], which is limited to 2 dimensions.
+#[derive(Clone, Hash, PartialEq)]
+pub struct GridN<const D: usize, T> {
+    items: Vec<T>,
+    min: Point<D, i32>,
+    size: Vector<D, i32>,
+}
+
+impl<const D: usize, T> GridN<D, T> {
+    /// Creates a new grid covering all points within the (inclusive) bounding box
+    pub fn new_default(value: T, bounds: BoundingBox<D, i32>) -> GridN<D, T>
+    where
+        T: Clone,
+    {
+        let size = bounds.total_size() + Vector { coords: [1; D] };
+        let len = size.coords.iter().map(|&c| c as usize).product();
+        GridN { items: vec![value; len], min: bounds.min_point(), size }
+    }
+
+    /// Creates a grid that fits all given points, where those points are set to `filled` and all
+    /// others to `empty`.
+    pub fn from_points<I>(points: I, filled: T, empty: T) -> GridN<D, T>
+    where
+        I: IntoIterator<Item = Point<D, i32>>,
+        T: Clone,
+    {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let mut min = *points.first().expect("Need at least a single point");
+        let mut max = min;
+        for point in &points {
+            for ix in 0..D {
+                min.coords[ix] = min.coords[ix].min(point.coords[ix]);
+                max.coords[ix] = max.coords[ix].max(point.coords[ix]);
+            }
+        }
+
+        let mut grid = GridN::new_default(empty, BoundingBox::from(min, max));
+        for point in points {
+            grid[point] = filled.clone();
+        }
+        grid
+    }
+
+    pub fn size(&self) -> Vector<D, i32> { self.size }
+
+    pub fn len(&self) -> usize { self.items.len() }
+
+    pub fn is_empty(&self) -> bool { self.items.is_empty() }
+
+    pub fn bounding_box(&self) -> BoundingBox<D, i32> {
+        BoundingBox::from(self.min, self.min + self.size - Vector { coords: [1; D] })
+    }
+
+    pub fn is_valid_location(&self, location: &Point<D, i32>) -> bool {
+        (0..D).all(|ix| (0..self.size.coords[ix]).contains(&(location.coords[ix] - self.min[ix])))
+    }
+
+    fn index_from_location(&self, location: &Point<D, i32>) -> Option<usize> {
+        if !self.is_valid_location(location) {
+            return None;
+        }
+
+        let mut index = 0usize;
+        for ix in (0..D).rev() {
+            index = index * self.size.coords[ix] as usize
+                + (location.coords[ix] - self.min.coords[ix]) as usize;
+        }
+        Some(index)
+    }
+
+    fn location_from_index(&self, mut index: usize) -> Point<D, i32> {
+        let mut location = self.min;
+        for ix in 0..D {
+            let size = self.size.coords[ix] as usize;
+            location.coords[ix] += (index % size) as i32;
+            index /= size;
+        }
+        location
+    }
+
+    pub fn get(&self, location: Point<D, i32>) -> Option<&T> {
+        let ix = self.index_from_location(&location)?;
+        self.items.get(ix)
+    }
+
+    pub fn get_mut(&mut self, location: Point<D, i32>) -> Option<&mut T> {
+        let ix = self.index_from_location(&location)?;
+        self.items.get_mut(ix)
+    }
+
+    /// All locations in the grid, where the first coordinate changes the fastest
+    pub fn locations(&self) -> impl Iterator<Item = Point<D, i32>> + use<'_, D, T> {
+        (0..self.items.len()).map(|ix| self.location_from_index(ix))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (Point<D, i32>, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(ix, value)| (self.location_from_index(ix), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> { self.items.iter() }
+
+    pub fn map<U, F>(&self, function: F) -> GridN<D, U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        GridN { items: self.items.iter().map(function).collect(), min: self.min, size: self.size }
+    }
+
+    /// Returns all neighbours of the location that are within the grid
+    pub fn neighbours(
+        &self,
+        location: Point<D, i32>,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (Point<D, i32>, &T)> {
+        connectivity.offsets::<D>().into_iter().flat_map(move |offset| {
+            let neighbour = location + offset;
+            self.get(neighbour).map(|value| (neighbour, value))
+        })
+    }
+
+    /// Grows the bounding box of the grid by `amount` in every direction, filling the new cells
+    /// with the given value.
+    pub fn grow(&mut self, amount: i32, value: T)
+    where
+        T: Clone,
+    {
+        let mut bounds = self.bounding_box();
+        bounds.expand(Vector { coords: [amount; D] });
+        self.resize_to(bounds, value);
+    }
+
+    /// Grows the bounding box of the grid such that it contains the given location, filling the
+    /// new cells with the given value.
+    pub fn grow_to_include(&mut self, location: Point<D, i32>, value: T)
+    where
+        T: Clone,
+    {
+        if self.is_valid_location(&location) {
+            return;
+        }
+
+        let bounds = self.bounding_box();
+        let mut min = bounds.min_point();
+        let mut max = bounds.max_point();
+        for ix in 0..D {
+            min.coords[ix] = min.coords[ix].min(location.coords[ix]);
+            max.coords[ix] = max.coords[ix].max(location.coords[ix]);
+        }
+        self.resize_to(BoundingBox::from(min, max), value);
+    }
+
+    fn resize_to(&mut self, bounds: BoundingBox<D, i32>, value: T)
+    where
+        T: Clone,
+    {
+        let mut grid = GridN::new_default(value, bounds);
+        let old_items = std::mem::take(&mut self.items);
+        for (ix, item) in old_items.into_iter().enumerate() {
+            let location = self.location_from_index(ix);
+            if let Some(target) = grid.get_mut(location) {
+                *target = item;
+            }
+        }
+        *self = grid;
+    }
+
+    /// Replaces the connected area of cells equal to the one at `start` with the given value.
+    /// Returns the number of cells that have been changed.
+    pub fn fill(&mut self, start: Point<D, i32>, value: T, connectivity: Connectivity) -> usize
+    where
+        T: PartialEq + Clone,
+    {
+        let accept_cell = match self.get(start) {
+            Some(cell) if *cell != value => cell.clone(),
+            _ => return 0,
+        };
+
+        let offsets = connectivity.offsets::<D>();
+        let mut count = 0;
+        let mut stack = vec![start];
+        while let Some(location) = stack.pop() {
+            match self.get_mut(location) {
+                Some(cell) if *cell == accept_cell => *cell = value.clone(),
+                _ => continue,
+            }
+            count += 1;
+            stack.extend(offsets.iter().map(|&offset| location + offset));
+        }
+
+        count
+    }
+
+    /// Takes a 2D slice through the grid. The `x_axis` and `y_axis` select which coordinates are
+    /// mapped to the x and y of the resulting grid, the other coordinates are taken from `at`.
+    pub fn slice(&self, x_axis: usize, y_axis: usize, at: Point<D, i32>) -> Grid<T>
+    where
+        T: Clone + Default,
+    {
+        let mut grid = Grid::new_empty(self.size.coords[x_axis], self.size.coords[y_axis]);
+        let mut location = at;
+        for y in 0..self.size.coords[y_axis] {
+            for x in 0..self.size.coords[x_axis] {
+                location.coords[x_axis] = self.min.coords[x_axis] + x;
+                location.coords[y_axis] = self.min.coords[y_axis] + y;
+                if let Some(value) = self.get(location) {
+                    grid[crate::geometry::point2(x, y)] = value.clone();
+                }
+            }
+        }
+        grid
+    }
+
+    /// Counts all faces of solid cells that do not touch another solid cell, including the faces
+    /// at the edge of the grid.
+    pub fn surface_area<F>(&self, is_solid: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        let offsets = Connectivity::Face.offsets::<D>();
+        self.entries()
+            .filter(|(_, value)| is_solid(value))
+            .map(|(location, _)| {
+                offsets
+                    .iter()
+                    .filter(|&&offset| !self.get(location + offset).is_some_and(&is_solid))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Counts the faces of solid cells that can be reached from outside of the grid, so this
+    /// ignores the surface of any enclosed air pockets.
+    pub fn exterior_surface_area<F>(&self, is_solid: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        let exterior = self.exterior(&is_solid);
+        let offsets = Connectivity::Face.offsets::<D>();
+        self.entries()
+            .filter(|(_, value)| is_solid(value))
+            .map(|(location, _)| {
+                offsets
+                    .iter()
+                    .filter(|&&offset| {
+                        exterior.get(location + offset).is_none_or(|&outside| outside)
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Returns a grid of the same size where all non-solid cells that are connected to the
+    /// outside of this grid are marked `true`.
+    pub fn exterior<F>(&self, is_solid: F) -> GridN<D, bool>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut result = self.map(|_| false);
+        let offsets = Connectivity::Face.offsets::<D>();
+        let mut stack = self
+            .locations()
+            .filter(|location| {
+                offsets.iter().any(|&offset| !self.is_valid_location(&(*location + offset)))
+            })
+            .collect::<Vec<_>>();
+
+        while let Some(location) = stack.pop() {
+            let Some(index) = self.index_from_location(&location) else {
+                continue;
+            };
+            if result.items[index] || is_solid(&self.items[index]) {
+                continue;
+            }
+            result.items[index] = true;
+            stack.extend(offsets.iter().map(|&offset| location + offset));
+        }
+
+        result
+    }
+}
+
+impl<const D: usize, T> Index<Point<D, i32>> for GridN<D, T> {
+    type Output = T;
+
+    fn index(&self, index: Point<D, i32>) -> &Self::Output { self.get(index).unwrap() }
+}
+
+impl<const D: usize, T> IndexMut<Point<D, i32>> for GridN<D, T> {
+    fn index_mut(&mut self, index: Point<D, i32>) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{point2, point3};
+
+    fn droplet() -> GridN<3, bool> {
+        let points = [
+            (2, 2, 2),
+            (1, 2, 2),
+            (3, 2, 2),
+            (2, 1, 2),
+            (2, 3, 2),
+            (2, 2, 1),
+            (2, 2, 3),
+            (2, 2, 4),
+            (2, 2, 6),
+            (1, 2, 5),
+            (3, 2, 5),
+            (2, 1, 5),
+            (2, 3, 5),
+        ];
+        GridN::from_points(points.map(|p| p.into()), true, false)
+    }
+
+    #[test]
+    fn neighbour_offsets() {
+        assert_eq!(6, Connectivity::Face.offsets::<3>().len());
+        assert_eq!(18, Connectivity::Edge.offsets::<3>().len());
+        assert_eq!(26, Connectivity::Corner.offsets::<3>().len());
+        assert_eq!(80, Connectivity::Corner.offsets::<4>().len());
+    }
+
+    #[test]
+    fn surface_areas() {
+        let grid = droplet();
+        assert_eq!(64, grid.surface_area(|&b| b));
+        assert_eq!(58, grid.exterior_surface_area(|&b| b));
+    }
+
+    #[test]
+    fn grow_keeps_values() {
+        let mut grid = droplet();
+        grid.grow_to_include(point3(-1, 0, 0), false);
+        assert_eq!(point3(-1, 0, 0), grid.bounding_box().min_point());
+        assert_eq!(64, grid.surface_area(|&b| b));
+        grid.grow(1, false);
+        assert_eq!(point3(4, 4, 7), grid.bounding_box().max_point());
+        assert_eq!(64, grid.surface_area(|&b| b));
+    }
+
+    #[test]
+    fn fill_and_slice() {
+        let mut grid = droplet().map(|&b| if b { b'#' } else { b'.' });
+        assert_eq!(40, grid.fill(point3(1, 1, 1), b'~', Connectivity::Face));
+        let slice = grid.slice(0, 1, point3(0, 0, 1));
+        assert_eq!(b'~', slice[point2(0, 0)]);
+        assert_eq!(b'#', slice[point2(1, 1)]);
+    }
+}
@@ -0,0 +1,490 @@
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::combinator::value;
+use nom::error::ParseError;
+use nom::{AsChar, Compare, IResult, Input, Parser};
+use nom_parse_trait::ParseFrom;
+use std::fmt::{Debug, Formatter};
+use std::iter::once;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// A location on a hexagonal lattice, stored in axial coordinates. The implicit third cube
+/// coordinate can be retrieved using [Hex::s] or by converting into a [Cube].
+#[derive(Copy, Clone, Default, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// The cube coordinate representation of a [Hex], where `q + r + s == 0` always holds.
+#[derive(Copy, Clone, Default, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct Cube {
+    pub q: i32,
+    pub r: i32,
+    pub s: i32,
+}
+
+pub const fn hex(q: i32, r: i32) -> Hex { Hex { q, r } }
+
+impl Debug for Hex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hex({},{})", self.q, self.r)
+    }
+}
+
+impl Debug for Cube {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cube({},{},{})", self.q, self.r, self.s)
+    }
+}
+
+impl From<Hex> for Cube {
+    fn from(value: Hex) -> Self { Cube { q: value.q, r: value.r, s: value.s() } }
+}
+
+impl From<Cube> for Hex {
+    fn from(value: Cube) -> Self { Hex { q: value.q, r: value.r } }
+}
+
+impl Hex {
+    pub const ORIGIN: Hex = hex(0, 0);
+
+    pub const fn s(&self) -> i32 { -self.q - self.r }
+
+    /// The number of steps needed to walk from the origin to this hex
+    pub const fn length(&self) -> i32 { (self.q.abs() + self.r.abs() + self.s().abs()) / 2 }
+
+    pub fn distance(&self, other: Hex) -> i32 { (*self - other).length() }
+
+    pub fn neighbours(&self) -> [Hex; 6] { PointyDirection::ALL.map(|dir| *self + dir) }
+
+    /// Rotates this hex 60 degrees clockwise around the origin
+    pub const fn rotate_right(self) -> Hex { hex(-self.r, -self.s()) }
+
+    /// Rotates this hex 60 degrees counter-clockwise around the origin
+    pub const fn rotate_left(self) -> Hex { hex(-self.s(), -self.q) }
+
+    /// All hexes at exactly the given distance from this one, starting at the south-western
+    /// corner and walking the ring clockwise. A ring of radius 0 only contains this hex.
+    pub fn ring(self, radius: i32) -> impl Iterator<Item = Hex> {
+        let start = self + hex(-radius, radius); // radius steps to the south-west
+        let steps = if radius > 0 { radius } else { 0 };
+        let sides = if radius > 0 { 6 } else { 0 };
+        let clockwise = [
+            PointyDirection::NW,
+            PointyDirection::NE,
+            PointyDirection::E,
+            PointyDirection::SE,
+            PointyDirection::SW,
+            PointyDirection::W,
+        ];
+
+        once(self).filter(move |_| radius == 0).chain(
+            (0..sides).flat_map(move |side| (0..steps).map(move |step| (side, step))).scan(
+                start,
+                move |current, (side, _)| {
+                    let result = *current;
+                    *current += clockwise[side];
+                    Some(result)
+                },
+            ),
+        )
+    }
+
+    /// All hexes within the given distance, starting with this hex and then spiraling out ring
+    /// by ring.
+    pub fn spiral(self, radius: i32) -> impl Iterator<Item = Hex> {
+        (0..=radius).flat_map(move |r| self.ring(r))
+    }
+
+    /// Returns the center of this hex in pixel space, where `size` is the distance between the
+    /// center and any corner of a hex.
+    pub fn to_pixel(&self, layout: HexLayout, size: f64) -> (f64, f64) {
+        let (q, r) = (self.q as f64, self.r as f64);
+        let sqrt3 = 3f64.sqrt();
+        match layout {
+            HexLayout::PointyTop => (size * (sqrt3 * q + sqrt3 / 2.0 * r), size * 1.5 * r),
+            HexLayout::FlatTop => (size * 1.5 * q, size * (sqrt3 / 2.0 * q + sqrt3 * r)),
+        }
+    }
+
+    /// Returns the hex that contains the given pixel, the inverse of [Hex::to_pixel]
+    pub fn from_pixel(x: f64, y: f64, layout: HexLayout, size: f64) -> Hex {
+        let sqrt3 = 3f64.sqrt();
+        let (q, r) = match layout {
+            HexLayout::PointyTop => ((sqrt3 / 3.0 * x - y / 3.0) / size, (2.0 / 3.0 * y) / size),
+            HexLayout::FlatTop => ((2.0 / 3.0 * x) / size, (-x / 3.0 + sqrt3 / 3.0 * y) / size),
+        };
+        Self::round(q, r)
+    }
+
+    /// Rounds fractional axial coordinates to the closest hex
+    pub fn round(q: f64, r: f64) -> Hex {
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        hex(rq as i32, rr as i32)
+    }
+}
+
+/// Determines how hexes are oriented when they are converted to pixels
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum HexLayout {
+    /// Hexes have a corner at the top, rows of hexes run from east to west
+    PointyTop,
+    /// Hexes have a flat edge at the top, columns of hexes run from north to south
+    FlatTop,
+}
+
+impl Add for Hex {
+    type Output = Hex;
+    fn add(self, rhs: Hex) -> Hex { hex(self.q + rhs.q, self.r + rhs.r) }
+}
+
+impl Add<PointyDirection> for Hex {
+    type Output = Hex;
+    fn add(self, rhs: PointyDirection) -> Hex { self + Hex::from(rhs) }
+}
+
+impl Add<FlatDirection> for Hex {
+    type Output = Hex;
+    fn add(self, rhs: FlatDirection) -> Hex { self + Hex::from(rhs) }
+}
+
+impl<V: Into<Hex>> AddAssign<V> for Hex {
+    fn add_assign(&mut self, rhs: V) { *self = *self + rhs.into(); }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+    fn sub(self, rhs: Hex) -> Hex { hex(self.q - rhs.q, self.r - rhs.r) }
+}
+
+impl Mul<i32> for Hex {
+    type Output = Hex;
+    fn mul(self, rhs: i32) -> Hex { hex(self.q * rhs, self.r * rhs) }
+}
+
+impl Neg for Hex {
+    type Output = Hex;
+    fn neg(self) -> Hex { hex(-self.q, -self.r) }
+}
+
+macro_rules! hex_direction {
+    ($name:ident; $($variant:ident = $text:literal ($q:expr, $r:expr)),*) => {
+        #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub const ALL: [$name; 6] = [$($name::$variant),*];
+
+            pub fn turn_left(self) -> $name { Self::ALL[(self as usize + 5) % 6] }
+
+            pub fn turn_right(self) -> $name { Self::ALL[(self as usize + 1) % 6] }
+        }
+
+        impl From<$name> for Hex {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => hex($q, $r)),*
+                }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> Self::Output { Self::ALL[(self as usize + 3) % 6] }
+        }
+
+        impl<I, E> ParseFrom<I, E> for $name
+        where
+            E: ParseError<I>,
+            I: Input + Compare<&'static str>,
+            <I as Input>::Item: AsChar,
+        {
+            fn parse(input: I) -> IResult<I, Self, E> {
+                // The two letter directions need to be tried first, since they share a prefix
+                let mut variants = $name::ALL;
+                variants.sort_by_key(|dir| std::cmp::Reverse(dir.name().len()));
+                let [a, b, c, d, e, f] = variants;
+                alt((
+                    value(a, tag_no_case(a.name())),
+                    value(b, tag_no_case(b.name())),
+                    value(c, tag_no_case(c.name())),
+                    value(d, tag_no_case(d.name())),
+                    value(e, tag_no_case(e.name())),
+                    value(f, tag_no_case(f.name())),
+                ))
+                .parse(input)
+            }
+        }
+
+        impl $name {
+            const fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => $text),*
+                }
+            }
+        }
+    };
+}
+
+// Directions for hexes with a corner at the top, listed clockwise starting east.
+hex_direction!(PointyDirection;
+    E = "e" (1, 0),
+    SE = "se" (0, 1),
+    SW = "sw" (-1, 1),
+    W = "w" (-1, 0),
+    NW = "nw" (0, -1),
+    NE = "ne" (1, -1)
+);
+
+// Directions for hexes with a flat edge at the top, listed clockwise starting north.
+hex_direction!(FlatDirection;
+    N = "n" (0, -1),
+    NE = "ne" (1, -1),
+    SE = "se" (1, 0),
+    S = "s" (0, 1),
+    SW = "sw" (-1, 1),
+    NW = "nw" (-1, 0)
+);
+
+/// Dense storage of values on a parallelogram of hexes, that covers all hexes with
+/// `q` in `min.q..min.q + width` and `r` in `min.r..min.r + height`.
+#[derive(Clone, Hash, PartialEq)]
+pub struct HexGrid<T> {
+    items: Vec<T>,
+    min: Hex,
+    width: i32,
+    height: i32,
+}
+
+impl<T> HexGrid<T> {
+    pub fn new_default(value: T, min: Hex, width: i32, height: i32) -> HexGrid<T>
+    where
+        T: Clone,
+    {
+        if width < 0 {
+            panic!("Width cannot be negative")
+        } else if height < 0 {
+            panic!("Height cannot be negative")
+        }
+        HexGrid { items: vec![value; (width * height) as usize], min, width, height }
+    }
+
+    /// Creates a grid that can store every hex within `radius` steps of the `center`
+    pub fn around(value: T, center: Hex, radius: i32) -> HexGrid<T>
+    where
+        T: Clone,
+    {
+        let size = 2 * radius + 1;
+        HexGrid::new_default(value, center - hex(radius, radius), size, size)
+    }
+
+    pub fn width(&self) -> i32 { self.width }
+
+    pub fn height(&self) -> i32 { self.height }
+
+    pub fn min(&self) -> Hex { self.min }
+
+    pub fn len(&self) -> usize { self.items.len() }
+
+    pub fn is_empty(&self) -> bool { self.items.is_empty() }
+
+    pub fn is_valid_location(&self, location: Hex) -> bool {
+        let offset = location - self.min;
+        (0..self.width).contains(&offset.q) && (0..self.height).contains(&offset.r)
+    }
+
+    fn index_from_location(&self, location: Hex) -> Option<usize> {
+        if self.is_valid_location(location) {
+            let offset = location - self.min;
+            Some((offset.q + offset.r * self.width) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn location_from_index(&self, index: usize) -> Hex {
+        let index = index as i32;
+        self.min + hex(index % self.width, index / self.width)
+    }
+
+    pub fn get(&self, location: Hex) -> Option<&T> {
+        let ix = self.index_from_location(location)?;
+        self.items.get(ix)
+    }
+
+    pub fn get_mut(&mut self, location: Hex) -> Option<&mut T> {
+        let ix = self.index_from_location(location)?;
+        self.items.get_mut(ix)
+    }
+
+    pub fn locations(&self) -> impl Iterator<Item = Hex> + use<'_, T> {
+        (0..self.items.len()).map(|ix| self.location_from_index(ix))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (Hex, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(ix, value)| (self.location_from_index(ix), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> { self.items.iter() }
+
+    pub fn neighbours(&self, location: Hex) -> impl Iterator<Item = (Hex, &T)> {
+        location
+            .neighbours()
+            .into_iter()
+            .flat_map(|n| self.get(n).map(|value| (n, value)))
+    }
+
+    pub fn map<U, F>(&self, function: F) -> HexGrid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        HexGrid {
+            items: self.items.iter().map(function).collect(),
+            min: self.min,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Renders each hex of the grid as a `size` pixel hexagon. Pixels that don't fall in the grid
+    /// are left transparent.
+    pub fn render_to_image(
+        &self,
+        filename: &str,
+        layout: HexLayout,
+        size: f64,
+        mapping: impl Fn(&T) -> [u8; 4],
+    ) {
+        let centers = self.locations().map(|loc| loc.to_pixel(layout, size)).collect::<Vec<_>>();
+        if centers.is_empty() {
+            return;
+        }
+        let min_x = centers.iter().map(|c| c.0).fold(f64::INFINITY, f64::min) - size;
+        let max_x = centers.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max) + size;
+        let min_y = centers.iter().map(|c| c.1).fold(f64::INFINITY, f64::min) - size;
+        let max_y = centers.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max) + size;
+
+        let mut image =
+            image::RgbaImage::new((max_x - min_x).ceil() as u32, (max_y - min_y).ceil() as u32);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let location =
+                Hex::from_pixel(x as f64 + min_x + 0.5, y as f64 + min_y + 0.5, layout, size);
+            if let Some(value) = self.get(location) {
+                *pixel = image::Rgba(mapping(value));
+            }
+        }
+
+        image
+            .save_with_format(filename, image::ImageFormat::Png)
+            .expect("Expect saving to not be a problem");
+    }
+}
+
+impl<T> std::ops::Index<Hex> for HexGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: Hex) -> &Self::Output { self.get(index).unwrap() }
+}
+
+impl<T> std::ops::IndexMut<Hex> for HexGrid<T> {
+    fn index_mut(&mut self, index: Hex) -> &mut Self::Output { self.get_mut(index).unwrap() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::Finish;
+    use nom::error::Error;
+    use nom::multi::many1;
+    use nom_parse_trait::ParseFromExt;
+
+    #[test]
+    fn distance_and_cube_conversion() {
+        assert_eq!(Cube { q: 1, r: -3, s: 2 }, Cube::from(hex(1, -3)));
+        assert_eq!(3, hex(1, -3).length());
+        assert_eq!(5, hex(2, 1).distance(hex(-1, -1)));
+    }
+
+    #[test]
+    fn walking_directions_in_a_circle_returns_home() {
+        let end = PointyDirection::ALL.into_iter().fold(Hex::ORIGIN, |loc, dir| loc + dir);
+        assert_eq!(Hex::ORIGIN, end);
+        assert_eq!(PointyDirection::SW, -PointyDirection::NE);
+        assert_eq!(FlatDirection::NE, FlatDirection::N.turn_right());
+    }
+
+    #[test]
+    fn turning_matches_rotation() {
+        assert_eq!(PointyDirection::SE, PointyDirection::E.turn_right());
+        assert_eq!(PointyDirection::NE, PointyDirection::E.turn_left());
+        for dir in PointyDirection::ALL {
+            assert_eq!(Hex::from(dir).rotate_right(), Hex::from(dir.turn_right()));
+            assert_eq!(Hex::from(dir).rotate_left(), Hex::from(dir.turn_left()));
+        }
+        for dir in FlatDirection::ALL {
+            assert_eq!(Hex::from(dir).rotate_right(), Hex::from(dir.turn_right()));
+            assert_eq!(Hex::from(dir).rotate_left(), Hex::from(dir.turn_left()));
+        }
+    }
+
+    #[test]
+    fn parse_directions() {
+        let result: Result<Vec<PointyDirection>, Error<&str>> = many1(PointyDirection::parse)
+            .parse_complete("esenwnew")
+            .finish()
+            .map(|(_, d)| d);
+        use PointyDirection::*;
+        assert_eq!(Ok(vec![E, SE, NW, NE, W]), result);
+
+        let result: Result<FlatDirection, Error<&str>> = FlatDirection::parse_complete("s");
+        assert_eq!(Ok(FlatDirection::S), result);
+    }
+
+    #[test]
+    fn rings_and_spirals() {
+        assert_eq!(vec![hex(2, 2)], hex(2, 2).ring(0).collect::<Vec<_>>());
+        let ring = hex(1, 1).ring(2).collect::<Vec<_>>();
+        assert_eq!(12, ring.len());
+        assert!(ring.iter().all(|h| h.distance(hex(1, 1)) == 2));
+        assert!(ring.iter().zip(ring.iter().cycle().skip(1)).all(|(a, b)| a.distance(*b) == 1));
+        assert_eq!(37, Hex::ORIGIN.spiral(3).count());
+    }
+
+    #[test]
+    fn pixel_round_trip() {
+        for layout in [HexLayout::PointyTop, HexLayout::FlatTop] {
+            for location in Hex::ORIGIN.spiral(4) {
+                let (x, y) = location.to_pixel(layout, 10.0);
+                assert_eq!(location, Hex::from_pixel(x, y, layout, 10.0));
+            }
+        }
+    }
+
+    #[test]
+    fn hex_grid_storage() {
+        let mut grid = HexGrid::around(0u8, Hex::ORIGIN, 2);
+        grid[hex(-2, 1)] = 5;
+        assert_eq!(Some(&5), grid.get(hex(-2, 1)));
+        assert_eq!(None, grid.get(hex(3, 0)));
+        assert_eq!(
+            1,
+            grid.neighbours(hex(-1, 1)).filter(|(_, v)| **v == 5).count()
+        );
+    }
+}
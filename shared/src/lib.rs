@@ -1,4 +1,5 @@
 pub mod builder;
+pub mod compressed;
 pub mod convert;
 pub mod day;
 pub mod direction;
@@ -6,6 +7,8 @@ pub mod disjoint_set;
 pub mod geometry;
 pub mod graph_utils;
 pub mod grid;
+pub mod grid_n;
+pub mod hex;
 pub mod iter_utils;
 pub mod key;
 pub mod linear_solver;
@@ -15,4 +18,5 @@ pub mod parsing;
 pub mod rgb;
 pub mod search;
 pub mod small_string;
+pub mod svg;
 pub mod test_utils;
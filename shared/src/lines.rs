@@ -1,6 +1,8 @@
 use crate::direction::Direction;
-use crate::geometry::{Point, Vector};
+use crate::geometry::{BoundingBox, Point, Vector};
+use crate::math::{Rational, gcd};
 use nom_parse_macros::parse_from;
+use num_traits::{PrimInt, Signed};
 use std::cmp::{max, min};
 use std::ops::Add;
 
@@ -59,3 +61,618 @@ impl<const D: usize, N: Add<Output = N> + Copy> Add<Vector<D, N>> for LineSegmen
         LineSegment { start: self.start + rhs, end: self.end + rhs }
     }
 }
+
+/// A simple polygon on integer coordinates, given by its corners in order. The last corner is
+/// connected back to the first one. Use `i128` when the coordinates are large enough for the
+/// products in the area calculation to overflow an `i64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polygon<T> {
+    vertices: Vec<Point<2, T>>,
+}
+
+impl<T: PrimInt + Signed> Polygon<T> {
+    pub fn new(vertices: Vec<Point<2, T>>) -> Polygon<T> { Polygon { vertices } }
+
+    /// Builds the polygon by following the moves from the start, e.g. the instructions of a
+    /// digger. When the moves end at the start again, that point is not repeated.
+    pub fn from_moves(
+        start: Point<2, T>,
+        moves: impl IntoIterator<Item = (Direction, T)>,
+    ) -> Polygon<T> {
+        let mut vertices = vec![start];
+        let mut current = start;
+        for (direction, length) in moves {
+            let step: Vector<2, T> = direction.into();
+            current = current + step * length;
+            vertices.push(current);
+        }
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point<2, T>] { &self.vertices }
+
+    /// All edges, including the one from the last vertex back to the first
+    pub fn edges(&self) -> impl Iterator<Item = LineSegment<2, T>> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().zip(next).map(|(&start, &end)| LineSegment { start, end })
+    }
+
+    /// Twice the area using the shoelace formula, which is always a whole number. It's positive
+    /// when the vertices go clockwise on a grid where y goes down (counter-clockwise with y up).
+    pub fn twice_signed_area(&self) -> T {
+        self.edges().fold(T::zero(), |area, edge| {
+            area + edge.start.x() * edge.end.y() - edge.end.x() * edge.start.y()
+        })
+    }
+
+    /// The area of the polygon, rounded down when it isn't a whole number
+    pub fn area(&self) -> T { self.twice_signed_area().abs() / (T::one() + T::one()) }
+
+    /// The number of points with whole coordinates on the edges
+    pub fn boundary_points(&self) -> T {
+        self.edges().fold(T::zero(), |count, edge| {
+            let step = edge.end - edge.start;
+            count + gcd(step.x(), step.y())
+        })
+    }
+
+    /// The number of points with whole coordinates strictly inside, using Pick's theorem
+    pub fn interior_points(&self) -> T {
+        let two = T::one() + T::one();
+        (self.twice_signed_area().abs() - self.boundary_points() + two) / two
+    }
+
+    /// The number of points with whole coordinates inside or on the edge. When the polygon goes
+    /// through the centers of grid cells, this is the number of cells that it covers.
+    pub fn enclosed_points(&self) -> T { self.interior_points() + self.boundary_points() }
+
+    /// Checks if all edges are horizontal or vertical
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|edge| edge.is_horizontal() || edge.is_vertical())
+    }
+
+    pub fn on_boundary(&self, point: Point<2, T>) -> bool { on_edges(self.edges(), point) }
+
+    /// How many times the polygon goes around the point, where the sign depends on the
+    /// direction. This is only meaningful for points that are not on the boundary.
+    pub fn winding_number(&self, point: Point<2, T>) -> i32 { winding_number(self.edges(), point) }
+
+    /// Checks if the point is inside or on the boundary using the winding number, which also
+    /// counts areas where a self-intersecting polygon overlaps itself as inside.
+    pub fn contains(&self, point: Point<2, T>) -> bool {
+        self.on_boundary(point) || self.winding_number(point) != 0
+    }
+
+    /// The same as [Polygon::contains], but for a point given in half steps, so it can also be
+    /// in the middle between whole coordinates
+    fn contains_doubled(&self, point: Point<2, T>) -> bool {
+        let two = T::one() + T::one();
+        let double = |p: Point<2, T>| Point { coords: p.coords.map(|c| c * two) };
+        let edges =
+            || self.edges().map(|e| LineSegment { start: double(e.start), end: double(e.end) });
+        on_edges(edges(), point) || winding_number(edges(), point) != 0
+    }
+
+    /// Checks if the point is inside or on the boundary using the even-odd rule, where areas
+    /// that a self-intersecting polygon covers an even number of times are outside.
+    pub fn contains_even_odd(&self, point: Point<2, T>) -> bool {
+        if self.on_boundary(point) {
+            return true;
+        }
+        let crossings = self
+            .edges()
+            .filter(|edge| (edge.start.y() > point.y()) != (edge.end.y() > point.y()))
+            .filter(|edge| {
+                let (along, to_point) = (edge.end - edge.start, point - edge.start);
+                // Is the point left of where the edge crosses the horizontal line through it
+                let (left, right) = (to_point.x() * along.y(), to_point.y() * along.x());
+                if along.y() > T::zero() { left < right } else { left > right }
+            })
+            .count();
+        crossings % 2 == 1
+    }
+
+    /// Checks if the whole rectangle with the given opposite corners is inside the polygon,
+    /// where touching the boundary counts as inside. Rectangles of a single row or column are
+    /// supported as well. Only works for rectilinear polygons.
+    pub fn contains_rectangle(&self, corner: Point<2, T>, opposite: Point<2, T>) -> bool {
+        assert!(
+            self.is_rectilinear(),
+            "Rectangle checks only work for rectilinear polygons"
+        );
+        if corner.x() == opposite.x() || corner.y() == opposite.y() {
+            return self.contains_segment(corner, opposite);
+        }
+
+        // Without an edge going through the inside of the rectangle, the inside is either
+        // completely in or out of the polygon, which the center tells. The border of the
+        // rectangle then follows from the inside.
+        let (min_x, max_x) = (min(corner.x(), opposite.x()), max(corner.x(), opposite.x()));
+        let (min_y, max_y) = (min(corner.y(), opposite.y()), max(corner.y(), opposite.y()));
+        let crossed = self.edges().any(|edge| {
+            if edge.is_horizontal() {
+                min_y < edge.start.y()
+                    && edge.start.y() < max_y
+                    && max(edge.min_x(), min_x) < min(edge.max_x(), max_x)
+            } else {
+                min_x < edge.start.x()
+                    && edge.start.x() < max_x
+                    && max(edge.min_y(), min_y) < min(edge.max_y(), max_y)
+            }
+        });
+        !crossed && self.contains_doubled(Point { coords: [min_x + max_x, min_y + max_y] })
+    }
+
+    /// Checks if a horizontal or vertical segment is inside. The boundary can only cross the
+    /// segment at the coordinates of the vertices, so after splitting it there a single point
+    /// tells if a part is inside.
+    fn contains_segment(&self, start: Point<2, T>, end: Point<2, T>) -> bool {
+        let axis = if start.y() == end.y() { 0 } else { 1 };
+        let (low, high) = (
+            min(start.coords[axis], end.coords[axis]),
+            max(start.coords[axis], end.coords[axis]),
+        );
+        let mut splits = self
+            .vertices
+            .iter()
+            .map(|vertex| vertex.coords[axis])
+            .filter(|&c| low < c && c < high)
+            .collect::<Vec<_>>();
+        splits.extend([low, high]);
+        splits.sort();
+        splits.dedup();
+
+        let two = T::one() + T::one();
+        let doubled_at = |doubled: T| {
+            let mut point = Point { coords: start.coords.map(|c| c * two) };
+            point.coords[axis] = doubled;
+            point
+        };
+        splits.iter().all(|&c| self.contains_doubled(doubled_at(c * two)))
+            && splits
+                .windows(2)
+                .all(|pair| self.contains_doubled(doubled_at(pair[0] + pair[1])))
+    }
+}
+
+fn on_edges<T: PrimInt + Signed>(
+    mut edges: impl Iterator<Item = LineSegment<2, T>>,
+    point: Point<2, T>,
+) -> bool {
+    edges.any(|edge| {
+        let (along, to_point) = (edge.end - edge.start, point - edge.start);
+        along.x() * to_point.y() == along.y() * to_point.x()
+            && (edge.min_x()..=edge.max_x()).contains(&point.x())
+            && (edge.min_y()..=edge.max_y()).contains(&point.y())
+    })
+}
+
+fn winding_number<T: PrimInt + Signed>(
+    edges: impl Iterator<Item = LineSegment<2, T>>,
+    point: Point<2, T>,
+) -> i32 {
+    let mut winding = 0;
+    for edge in edges {
+        let (along, to_point) = (edge.end - edge.start, point - edge.start);
+        let side = along.x() * to_point.y() - to_point.x() * along.y();
+        if edge.start.y() <= point.y() {
+            if edge.end.y() > point.y() && side > T::zero() {
+                winding += 1;
+            }
+        } else if edge.end.y() <= point.y() && side < T::zero() {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// A point with exact fractional coordinates, e.g. where two lines cross
+pub type RationalPoint<const D: usize, T> = Point<D, Rational<T>>;
+
+/// Where two lines (or half-lines) meet, see [Line::intersection]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intersection<const D: usize, T> {
+    /// The lines cross in a single point, which is at parameter `t` of the first line and `u` of
+    /// the second one.
+    Point { point: RationalPoint<D, T>, t: Rational<T>, u: Rational<T> },
+    /// Both lines are the same line (for half-lines: they are on the same line and share some
+    /// points)
+    Collinear,
+    /// The lines go in the same direction, but never meet
+    Parallel,
+    /// The lines are not parallel, but still never meet. In 3D this happens for skew lines, and
+    /// for half-lines when the crossing would be behind the start of one of them, or when they
+    /// are on the same line but point away from each other.
+    Disjoint,
+}
+
+impl<const D: usize, T: PrimInt + Signed> Intersection<D, T> {
+    pub fn point(&self) -> Option<RationalPoint<D, T>> {
+        match self {
+            Intersection::Point { point, .. } => Some(*point),
+            _ => None,
+        }
+    }
+}
+
+/// A line through `origin` in the `direction`, where each point is `origin + t * direction`. All
+/// calculations are exact, so use `i128` for large coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line<const D: usize, T> {
+    pub origin: Point<D, T>,
+    pub direction: Vector<D, T>,
+}
+
+/// Like a [Line], but it only contains the points with `t >= 0`, e.g. a trajectory from a start
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HalfLine<const D: usize, T> {
+    pub origin: Point<D, T>,
+    pub direction: Vector<D, T>,
+}
+
+/// Checks if all 2x2 minors are zero, which means that the vectors are parallel
+fn is_parallel<const D: usize, T: PrimInt + Signed>(a: Vector<D, T>, b: Vector<D, T>) -> bool {
+    (0..D).all(|i| (i + 1..D).all(|j| a[i] * b[j] == a[j] * b[i]))
+}
+
+fn dot<const D: usize, T: PrimInt + Signed>(a: Vector<D, T>, b: Vector<D, T>) -> T {
+    (0..D).fold(T::zero(), |sum, ix| sum + a[ix] * b[ix])
+}
+
+impl<const D: usize, T: PrimInt + Signed> Line<D, T> {
+    pub fn new(origin: Point<D, T>, direction: Vector<D, T>) -> Line<D, T> {
+        assert!(
+            direction.coords.iter().any(|c| !c.is_zero()),
+            "A line needs a direction"
+        );
+        Line { origin, direction }
+    }
+
+    pub fn through(first: Point<D, T>, second: Point<D, T>) -> Line<D, T> {
+        Line::new(first, second - first)
+    }
+
+    pub fn at(&self, t: Rational<T>) -> RationalPoint<D, T> {
+        let mut coords = [Rational::from(T::zero()); D];
+        for (ix, coord) in coords.iter_mut().enumerate() {
+            *coord = Rational::from(self.origin[ix]) + t * Rational::from(self.direction[ix]);
+        }
+        Point { coords }
+    }
+
+    pub fn is_parallel(&self, other: &Line<D, T>) -> bool {
+        is_parallel(self.direction, other.direction)
+    }
+
+    pub fn is_collinear(&self, other: &Line<D, T>) -> bool {
+        self.is_parallel(other) && is_parallel(self.direction, other.origin - self.origin)
+    }
+
+    /// Finds where the lines cross, with the exact location and the parameters of both lines
+    pub fn intersection(&self, other: &Line<D, T>) -> Intersection<D, T> {
+        let (r, s, diff) = (self.direction, other.direction, other.origin - self.origin);
+
+        // Solve origin + t * r = other.origin + u * s on two axes where the lines aren't parallel
+        let Some((i, j, det)) = (0..D)
+            .flat_map(|i| (i + 1..D).map(move |j| (i, j, r[i] * s[j] - r[j] * s[i])))
+            .find(|(_, _, det)| !det.is_zero())
+        else {
+            return if self.is_collinear(other) {
+                Intersection::Collinear
+            } else {
+                Intersection::Parallel
+            };
+        };
+        let t = Rational::new(diff[i] * s[j] - diff[j] * s[i], det);
+        let u = Rational::new(diff[i] * r[j] - diff[j] * r[i], det);
+
+        // The other axes also need to match, which is not the case for skew lines
+        let point = self.at(t);
+        if point != other.at(u) {
+            return Intersection::Disjoint;
+        }
+        Intersection::Point { point, t, u }
+    }
+
+    /// The parameters on both lines of the points where they are closest to each other, or
+    /// `None` when the lines are parallel. For lines that cross, these are the crossing point.
+    pub fn closest_approach(&self, other: &Line<D, T>) -> Option<(Rational<T>, Rational<T>)> {
+        let (r, s, w) = (self.direction, other.direction, self.origin - other.origin);
+        let (a, b, c) = (dot(r, r), dot(r, s), dot(s, s));
+        let (d, e) = (dot(r, w), dot(s, w));
+        let denominator = a * c - b * b;
+        if denominator.is_zero() {
+            return None;
+        }
+        Some((
+            Rational::new(b * e - c * d, denominator),
+            Rational::new(a * e - b * d, denominator),
+        ))
+    }
+
+    /// The closest points on both lines, see [Line::closest_approach]
+    pub fn closest_points(
+        &self,
+        other: &Line<D, T>,
+    ) -> Option<(RationalPoint<D, T>, RationalPoint<D, T>)> {
+        let (t, u) = self.closest_approach(other)?;
+        Some((self.at(t), other.at(u)))
+    }
+
+    /// The point where the lines cross, if that is inside the bounding box (inclusive)
+    pub fn intersection_within(
+        &self,
+        other: &Line<D, T>,
+        bounds: &BoundingBox<D, T>,
+    ) -> Option<RationalPoint<D, T>> {
+        let point = self.intersection(other).point()?;
+        contains_rational(bounds, &point).then_some(point)
+    }
+}
+
+fn contains_rational<const D: usize, T: PrimInt + Signed>(
+    bounds: &BoundingBox<D, T>,
+    point: &RationalPoint<D, T>,
+) -> bool {
+    let (min, max) = (bounds.min_point(), bounds.max_point());
+    (0..D).all(|ix| Rational::from(min[ix]) <= point[ix] && point[ix] <= Rational::from(max[ix]))
+}
+
+impl<const D: usize, T: PrimInt + Signed> HalfLine<D, T> {
+    pub fn new(origin: Point<D, T>, direction: Vector<D, T>) -> HalfLine<D, T> {
+        Line::new(origin, direction).into()
+    }
+
+    pub fn line(&self) -> Line<D, T> { Line { origin: self.origin, direction: self.direction } }
+
+    /// Finds where the half-lines cross, which needs to be at or after the start of both of them
+    pub fn intersection(&self, other: &HalfLine<D, T>) -> Intersection<D, T> {
+        match self.line().intersection(&other.line()) {
+            Intersection::Point { t, u, .. }
+                if t < Rational::from(T::zero()) || u < Rational::from(T::zero()) =>
+            {
+                Intersection::Disjoint
+            }
+            // On the same line, they overlap when one of them starts on the other one
+            Intersection::Collinear
+                if dot(other.origin - self.origin, self.direction) < T::zero()
+                    && dot(self.origin - other.origin, other.direction) < T::zero() =>
+            {
+                Intersection::Disjoint
+            }
+            intersection => intersection,
+        }
+    }
+
+    /// The point where the half-lines cross, if that is inside the bounding box (inclusive)
+    pub fn intersection_within(
+        &self,
+        other: &HalfLine<D, T>,
+        bounds: &BoundingBox<D, T>,
+    ) -> Option<RationalPoint<D, T>> {
+        let point = self.intersection(other).point()?;
+        contains_rational(bounds, &point).then_some(point)
+    }
+}
+
+impl<const D: usize, T> From<Line<D, T>> for HalfLine<D, T> {
+    fn from(line: Line<D, T>) -> Self {
+        HalfLine { origin: line.origin, direction: line.direction }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HalfLine, Intersection, Line, Polygon};
+    use crate::direction::Direction::*;
+    use crate::geometry::{BoundingBox, Point, point2, point3, vector2, vector3};
+    use crate::math::Rational;
+
+    #[test]
+    fn lagoon_from_moves() {
+        let moves = [
+            (East, 6),
+            (South, 5),
+            (West, 2),
+            (South, 2),
+            (East, 2),
+            (South, 2),
+            (West, 5),
+            (North, 2),
+            (West, 1),
+            (North, 2),
+            (East, 2),
+            (North, 3),
+            (West, 2),
+            (North, 2),
+        ];
+        let lagoon = Polygon::<i64>::from_moves(point2(0, 0), moves);
+        assert_eq!(14, lagoon.vertices().len());
+        assert!(lagoon.is_rectilinear());
+        assert_eq!(38, lagoon.boundary_points());
+        assert_eq!(24, lagoon.interior_points());
+        assert_eq!(62, lagoon.enclosed_points());
+    }
+
+    #[test]
+    fn large_coordinates() {
+        let moves = [
+            (East, 461937),
+            (South, 56407),
+            (East, 356671),
+            (South, 863240),
+            (East, 367720),
+            (South, 266681),
+            (West, 577262),
+            (North, 829975),
+            (West, 112010),
+            (South, 829975),
+            (West, 491645),
+            (North, 686074),
+            (West, 5411),
+            (North, 500254),
+        ];
+        let lagoon = Polygon::<i128>::from_moves(point2(0, 0), moves);
+        assert_eq!(952408144115, lagoon.enclosed_points());
+    }
+
+    #[test]
+    fn area_and_lattice_points() {
+        let triangle = Polygon::<i64>::new(vec![point2(0, 0), point2(4, 0), point2(0, 3)]);
+        assert!(!triangle.is_rectilinear());
+        assert_eq!(12, triangle.twice_signed_area());
+        assert_eq!(6, triangle.area());
+        assert_eq!(8, triangle.boundary_points());
+        assert_eq!(3, triangle.interior_points());
+
+        let reversed = Polygon::<i64>::new(triangle.vertices().iter().rev().copied().collect());
+        assert_eq!(-12, reversed.twice_signed_area());
+        assert_eq!(3, reversed.interior_points());
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let shape = Polygon::<i64>::new(vec![
+            point2(0, 0),
+            point2(4, 0),
+            point2(4, 2),
+            point2(2, 2),
+            point2(2, 4),
+            point2(0, 4),
+        ]);
+        assert!(shape.contains(point2(1, 1)));
+        assert!(shape.contains(point2(3, 2)));
+        assert!(shape.on_boundary(point2(3, 2)));
+        assert!(!shape.contains(point2(3, 3)));
+        assert!(!shape.contains(point2(5, 1)));
+        assert_eq!(1, shape.winding_number(point2(1, 3)).abs());
+        assert!(shape.contains_even_odd(point2(1, 3)));
+        assert!(!shape.contains_even_odd(point2(3, 3)));
+
+        // A pentagram covers its center twice, which is only inside when counting windings
+        let star = Polygon::<i64>::new(vec![
+            point2(0, -10),
+            point2(6, 8),
+            point2(-9, -3),
+            point2(9, -3),
+            point2(-6, 8),
+        ]);
+        assert_eq!(2, star.winding_number(point2(0, 0)).abs());
+        assert!(star.contains(point2(0, 0)));
+        assert!(!star.contains_even_odd(point2(0, 0)));
+        assert!(star.contains_even_odd(point2(0, -7)));
+    }
+
+    #[test]
+    fn rectangles_inside() {
+        let shape = Polygon::<i64>::new(vec![
+            point2(0, 0),
+            point2(6, 0),
+            point2(6, 4),
+            point2(4, 4),
+            point2(4, 1),
+            point2(2, 1),
+            point2(2, 4),
+            point2(0, 4),
+        ]);
+        assert!(shape.contains_rectangle(point2(0, 0), point2(6, 1)));
+        assert!(shape.contains_rectangle(point2(4, 4), point2(6, 0)));
+        assert!(!shape.contains_rectangle(point2(0, 2), point2(6, 3)));
+        assert!(!shape.contains_rectangle(point2(0, 0), point2(6, 4)));
+        // All corners are on the boundary around the gap
+        assert!(!shape.contains_rectangle(point2(2, 1), point2(4, 4)));
+
+        // Single rows and columns
+        assert!(!shape.contains_rectangle(point2(0, 2), point2(6, 2)));
+        assert!(!shape.contains_rectangle(point2(0, 4), point2(6, 4)));
+        assert!(shape.contains_rectangle(point2(6, 1), point2(0, 1)));
+        assert!(shape.contains_rectangle(point2(5, 0), point2(5, 4)));
+        assert!(!shape.contains_rectangle(point2(3, 0), point2(3, 2)));
+        assert!(shape.contains_rectangle(point2(3, 0), point2(3, 1)));
+        assert!(!shape.contains_rectangle(point2(3, 2), point2(3, 2)));
+    }
+
+    fn rational_point(coords: [(i64, i64); 2]) -> Point<2, Rational<i64>> {
+        Point { coords: coords.map(|(n, d)| Rational::new(n, d)) }
+    }
+
+    #[test]
+    fn crossing_lines() {
+        let first = Line::<2, i64>::through(point2(0, 0), point2(3, 1));
+        let second = Line::new(point2(0, 2), vector2(1, -1));
+        let Intersection::Point { point, t, u } = first.intersection(&second) else {
+            panic!("Expected the lines to cross");
+        };
+        assert_eq!(rational_point([(3, 2), (1, 2)]), point);
+        assert_eq!((Rational::new(1, 2), Rational::new(3, 2)), (t, u));
+
+        let shifted = Line::new(point2(1, 5), vector2(6, 2));
+        assert_eq!(Intersection::Parallel, first.intersection(&shifted));
+        let same = Line::new(point2(-3, -1), vector2(-6, -2));
+        assert_eq!(Intersection::Collinear, first.intersection(&same));
+        assert!(first.is_parallel(&shifted) && !first.is_collinear(&shifted));
+    }
+
+    #[test]
+    fn hailstone_paths() {
+        let bounds = BoundingBox::from(point2(7i128, 7), point2(27, 27));
+        let a = HalfLine::new(point2(19i128, 13), vector2(-2, 1));
+        let b = HalfLine::new(point2(18, 19), vector2(-1, -1));
+        let c = HalfLine::new(point2(20, 19), vector2(1, -5));
+        let d = HalfLine::new(point2(12, 31), vector2(-1, -2));
+
+        let point = a.intersection_within(&b, &bounds).unwrap();
+        assert_eq!(
+            (Rational::new(43, 3), Rational::new(46, 3)),
+            (point.x(), point.y())
+        );
+        assert_eq!(None, a.intersection_within(&d, &bounds)); // Outside the area
+        assert_eq!(Intersection::Disjoint, a.intersection(&c)); // In the past for both
+        assert!(a.line().intersection(&c.line()).point().is_some());
+        assert_eq!(
+            Intersection::Parallel,
+            b.intersection(&HalfLine::new(point2(20, 25), vector2(-2, -2)))
+        );
+    }
+
+    #[test]
+    fn half_lines_on_the_same_line() {
+        let half_line = HalfLine::new(point2(0i64, 0), vector2(2, 1));
+        let ahead = HalfLine::new(point2(4, 2), vector2(2, 1));
+        let towards = HalfLine::new(point2(4, 2), vector2(-2, -1));
+        let away = HalfLine::new(point2(-2, -1), vector2(-2, -1));
+        assert_eq!(Intersection::Collinear, half_line.intersection(&ahead));
+        assert_eq!(Intersection::Collinear, ahead.intersection(&half_line));
+        assert_eq!(Intersection::Collinear, half_line.intersection(&towards));
+        assert_eq!(Intersection::Disjoint, half_line.intersection(&away));
+        assert_eq!(Intersection::Disjoint, away.intersection(&half_line));
+        assert_eq!(
+            Intersection::Collinear,
+            half_line.line().intersection(&away.line())
+        );
+    }
+
+    #[test]
+    fn skew_lines_in_3d() {
+        let first = Line::<3, i64>::new(point3(0, 0, 0), vector3(1, 0, 0));
+        let second = Line::new(point3(2, -1, 3), vector3(0, 1, 0));
+        assert_eq!(Intersection::Disjoint, first.intersection(&second));
+        let (on_first, on_second) = first.closest_points(&second).unwrap();
+        assert_eq!([2, 0, 0].map(Rational::from), on_first.coords);
+        assert_eq!([2, 0, 3].map(Rational::from), on_second.coords);
+
+        let crossing = Line::new(point3(2, -1, 0), vector3(0, 2, 0));
+        let Intersection::Point { point, u, .. } = first.intersection(&crossing) else {
+            panic!("Expected the lines to cross");
+        };
+        assert_eq!([2, 0, 0].map(Rational::from), point.coords);
+        assert_eq!(Rational::new(1, 2), u);
+        assert_eq!(
+            None,
+            first.closest_approach(&Line::new(point3(0, 1, 1), vector3(-2, 0, 0)))
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use std::ops::Sub;
+use num_traits::{One, PrimInt, Signed};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 pub fn greatest_common_divisor<N>(left: N, right: N) -> N
 where
@@ -10,3 +13,165 @@ where
         std::cmp::Ordering::Greater => greatest_common_divisor(left - right, right),
     }
 }
+
+/// The greatest common divisor using Euclid's algorithm, which (unlike
+/// [greatest_common_divisor]) is also fast for large values and works with zero and negative
+/// values. The result is never negative.
+pub fn gcd<N: PrimInt + Signed>(left: N, right: N) -> N {
+    let (mut a, mut b) = (left.abs(), right.abs());
+    while !b.is_zero() {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// An exact fraction of two integers, which is always kept in its simplest form with a positive
+/// denominator. Use `i128` when the numerators and denominators can get large.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational<N> {
+    numerator: N,
+    denominator: N,
+}
+
+impl<N: PrimInt + Signed> Rational<N> {
+    pub fn new(numerator: N, denominator: N) -> Rational<N> {
+        assert!(!denominator.is_zero(), "The denominator cannot be zero");
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Rational { numerator: numerator / divisor, denominator: denominator / divisor }
+    }
+
+    pub fn numerator(&self) -> N { self.numerator }
+
+    pub fn denominator(&self) -> N { self.denominator }
+
+    pub fn is_integer(&self) -> bool { self.denominator.is_one() }
+
+    /// The value as an integer, if it is one
+    pub fn to_integer(&self) -> Option<N> { self.is_integer().then_some(self.numerator) }
+
+    /// The largest integer that is not larger than this value
+    pub fn floor(&self) -> N {
+        let quotient = self.numerator / self.denominator;
+        if self.numerator < N::zero() && !(self.numerator % self.denominator).is_zero() {
+            quotient - N::one()
+        } else {
+            quotient
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator.to_f64().unwrap() / self.denominator.to_f64().unwrap()
+    }
+}
+
+impl<N: PrimInt + Signed> From<N> for Rational<N> {
+    fn from(value: N) -> Self { Rational { numerator: value, denominator: N::one() } }
+}
+
+impl<N: PrimInt + Signed> Add for Rational<N> {
+    type Output = Rational<N>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Rational::new(
+            self.numerator * rhs.denominator + rhs.numerator * self.denominator,
+            self.denominator * rhs.denominator,
+        )
+    }
+}
+
+impl<N: PrimInt + Signed> Sub for Rational<N> {
+    type Output = Rational<N>;
+
+    fn sub(self, rhs: Self) -> Self::Output { self + -rhs }
+}
+
+impl<N: PrimInt + Signed> Mul for Rational<N> {
+    type Output = Rational<N>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational::new(
+            self.numerator * rhs.numerator,
+            self.denominator * rhs.denominator,
+        )
+    }
+}
+
+impl<N: PrimInt + Signed> Div for Rational<N> {
+    type Output = Rational<N>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Rational::new(
+            self.numerator * rhs.denominator,
+            self.denominator * rhs.numerator,
+        )
+    }
+}
+
+impl<N: PrimInt + Signed> Neg for Rational<N> {
+    type Output = Rational<N>;
+
+    fn neg(self) -> Self::Output {
+        Rational { numerator: -self.numerator, denominator: self.denominator }
+    }
+}
+
+impl<N: PrimInt + Signed> PartialOrd for Rational<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<N: PrimInt + Signed> Ord for Rational<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Both denominators are positive, so this keeps the order
+        (self.numerator * other.denominator).cmp(&(other.numerator * self.denominator))
+    }
+}
+
+impl<N: Display + One + PartialEq> Display for Rational<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.denominator.is_one() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl<N: Debug> Debug for Rational<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}/{:?}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rational, gcd};
+
+    #[test]
+    fn euclid_gcd() {
+        assert_eq!(6, gcd(48i64, -18));
+        assert_eq!(7, gcd(0i64, 7));
+        assert_eq!(0, gcd(0i64, 0));
+        assert_eq!(1, gcd(1_000_000_000_007i128, 3));
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        let half = Rational::new(2i64, 4);
+        let third = Rational::new(-1i64, -3);
+        assert_eq!((1, 2), (half.numerator(), half.denominator()));
+        assert_eq!(Rational::new(5, 6), half + third);
+        assert_eq!(Rational::new(1, 6), half - third);
+        assert_eq!(Rational::new(1, 6), half * third);
+        assert_eq!(Rational::new(3, 2), half / third);
+        assert_eq!(Rational::new(-1, 2), -half);
+        assert_eq!(Rational::new(1, -2), -half);
+        assert!(third < half && -half < third);
+        assert_eq!(Some(2), (half * Rational::from(4)).to_integer());
+        assert_eq!(None, half.to_integer());
+        assert_eq!((0, -1), (half.floor(), (-half).floor()));
+        assert_eq!(
+            "-7/3 3",
+            format!("{} {}", Rational::new(14i64, -6), Rational::from(3i64))
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use std::cmp::Reverse;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::hash::{BuildHasher, Hash};
 use std::ops::Add;
 
+use crate::grid::{Location, LocationSet};
 use fxhash::FxBuildHasher;
 use priority_queue::PriorityQueue;
 
@@ -42,21 +43,32 @@ pub fn a_star_search<G: SearchGraphWithGoal>(
     graph: &G,
     start_node: G::Node,
 ) -> Option<Vec<G::Node>> {
+    a_star_search_from(graph, [start_node]).map(|(_, path)| path)
+}
+
+/// Searches for the cheapest path from any of the start nodes to a goal node. Returns the score of
+/// that path together with the path itself, which like [a_star_search] starts at the goal node.
+pub fn a_star_search_from<G: SearchGraphWithGoal>(
+    graph: &G,
+    start_nodes: impl IntoIterator<Item = G::Node>,
+) -> Option<(G::Score, Vec<G::Node>)> {
     let mut current_states =
         HashMap::with_capacity_and_hasher(graph.expected_state_size(), FxBuildHasher::default());
-    current_states.insert(start_node, (G::Score::default(), None)); // the value is the score + where the node came from
     let mut open_set = PriorityQueue::with_capacity_and_hasher(
         graph.expected_state_size(),
         FxBuildHasher::default(),
     );
-    open_set.push(start_node, Reverse(G::Score::default())); // open_set uses the f_score as priority
+    for start_node in start_nodes {
+        current_states.insert(start_node, (G::Score::default(), None)); // the value is the score + where the node came from
+        open_set.push(start_node, Reverse(G::Score::default())); // open_set uses the f_score as priority
+    }
 
     while let Some((node, _)) = open_set.pop() {
+        let &(node_g_score, _) = current_states.get(&node).unwrap();
         if graph.is_goal(node) {
-            return Some(reconstruct_path(node, current_states));
+            return Some((node_g_score, reconstruct_path(node, current_states)));
         }
 
-        let &(node_g_score, _) = current_states.get(&node).unwrap();
         for (neighbour, distance) in graph.neighbours(node) {
             let new_g_score: G::Score = node_g_score + distance;
             let current_state = current_states.get(&neighbour);
@@ -109,6 +121,61 @@ where
     }
 }
 
+/// Keeps track of the states that have been visited during a search. For grid locations a
+/// [LocationSet] avoids the hashing that a [HashSet] needs.
+pub trait VisitedSet<S> {
+    /// Marks the state as visited, returns `true` if it wasn't visited before
+    fn visit(&mut self, state: S) -> bool;
+
+    fn is_visited(&self, state: &S) -> bool;
+}
+
+impl<S: Eq + Hash, H: BuildHasher> VisitedSet<S> for HashSet<S, H> {
+    fn visit(&mut self, state: S) -> bool { self.insert(state) }
+
+    fn is_visited(&self, state: &S) -> bool { self.contains(state) }
+}
+
+impl<S: Ord> VisitedSet<S> for BTreeSet<S> {
+    fn visit(&mut self, state: S) -> bool { self.insert(state) }
+
+    fn is_visited(&self, state: &S) -> bool { self.contains(state) }
+}
+
+impl VisitedSet<Location> for LocationSet {
+    fn visit(&mut self, state: Location) -> bool { self.insert(state) }
+
+    fn is_visited(&self, state: &Location) -> bool { self.contains(state) }
+}
+
+/// Visits all states that can be reached from the start state depth first, where each state is
+/// only visited once. Afterward, the visited set contains all reachable states.
+pub fn depth_first_search_visited<S, I, FN, V>(start_state: S, neighbours: FN, visited: &mut V)
+where
+    S: Clone,
+    I: IntoIterator<Item = S>,
+    FN: Fn(S) -> I,
+    V: VisitedSet<S>,
+{
+    if visited.visit(start_state.clone()) {
+        depth_first_search(start_state, neighbours, |state| visited.visit(state));
+    }
+}
+
+/// Visits all states that can be reached from the start state breadth first, where each state is
+/// only visited once. Afterward, the visited set contains all reachable states.
+pub fn breadth_first_search_visited<S, I, FN, V>(start_state: S, neighbours: FN, visited: &mut V)
+where
+    S: Clone,
+    I: IntoIterator<Item = S>,
+    FN: Fn(S) -> I,
+    V: VisitedSet<S>,
+{
+    if visited.visit(start_state.clone()) {
+        breadth_first_search(start_state, neighbours, |state| visited.visit(state));
+    }
+}
+
 pub fn find_max_nonoverlapping_combination<T, S>(
     input: impl Iterator<Item = (u64, T)>,
     get_score: fn(&T) -> S,
@@ -127,7 +194,7 @@ where
         buckets.push(Vec::with_capacity(32));
     }
     let mut input = input.collect::<Vec<_>>();
-    input.sort_by(|l, r| get_score(&r.1).cmp(&get_score(&l.1)));
+    input.sort_by_key(|item| Reverse(get_score(&item.1)));
     input.iter().for_each(|item| buckets[item.0 as usize & split_mask].push(item));
 
     let mut max: S = Default::default();
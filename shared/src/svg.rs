@@ -0,0 +1,237 @@
+//! Writing grids, paths and shapes as SVG, which (unlike a PNG) stays sharp when zooming in on a
+//! large puzzle. Each grid cell is one unit in size, so the cell at (x, y) covers the square from
+//! (x, y) to (x + 1, y + 1). Other shapes are drawn at their exact coordinates.
+
+use crate::geometry::{BoundingBox, Point};
+use crate::grid::{Grid, Location};
+use crate::lines::LineSegment;
+use num_traits::ToPrimitive;
+use std::fmt::{Display, Write};
+
+/// Collects SVG elements in the order in which they are added. The view box is calculated from
+/// everything that has been drawn, so the output only depends on what has been added.
+#[derive(Debug, Clone, Default)]
+pub struct Svg {
+    elements: Vec<String>,
+    min: Option<(f64, f64)>,
+    max: Option<(f64, f64)>,
+    margin: f64,
+}
+
+fn coord<N: ToPrimitive>(value: N) -> f64 {
+    value.to_f64().expect("Expect coordinates to fit in a f64")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Svg {
+    pub fn new() -> Self { Svg::default() }
+
+    /// Adds empty space around the drawing in the view box
+    pub fn margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    fn include(&mut self, x: f64, y: f64) {
+        self.min = Some(self.min.map_or((x, y), |(mx, my)| (mx.min(x), my.min(y))));
+        self.max = Some(self.max.map_or((x, y), |(mx, my)| (mx.max(x), my.max(y))));
+    }
+
+    /// Draws each cell for which the style function returns a style as a square. Cells next to
+    /// each other in a row with the same style are merged into a single rectangle.
+    pub fn grid<T, S, F>(&mut self, grid: &Grid<T>, style: F) -> &mut Self
+    where
+        S: Display + PartialEq,
+        F: Fn(&T) -> Option<S>,
+    {
+        self.include(0., 0.);
+        self.include(grid.width() as f64, grid.height() as f64);
+        for row in grid.east_lines() {
+            let mut run: Option<(Location, i32, S)> = None;
+            for (location, value) in row.map(|(location, value)| (location, style(value))) {
+                match (&mut run, value) {
+                    (Some((_, width, current)), Some(next)) if *current == next => *width += 1,
+                    (_, next) => {
+                        if let Some((start, width, current)) = run.take() {
+                            self.add_rect(start, width, current);
+                        }
+                        run = next.map(|next| (location, 1, next));
+                    }
+                }
+            }
+            if let Some((start, width, current)) = run {
+                self.add_rect(start, width, current);
+            }
+        }
+        self
+    }
+
+    fn add_rect(&mut self, start: Location, width: i32, style: impl Display) {
+        self.elements.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{width}\" height=\"1\" style=\"{style}\"/>",
+            start.x(),
+            start.y()
+        ));
+    }
+
+    /// Draws a line through the centers of the given grid cells, e.g. a path found by a search
+    pub fn path<'a>(
+        &mut self,
+        locations: impl IntoIterator<Item = &'a Location>,
+        style: impl Display,
+    ) -> &mut Self {
+        let points = locations
+            .into_iter()
+            .map(|location| (location.x() as f64 + 0.5, location.y() as f64 + 0.5))
+            .collect::<Vec<_>>();
+        self.add_polyline(points, style)
+    }
+
+    /// Draws a line through the given points
+    pub fn polyline<'a, N>(
+        &mut self,
+        points: impl IntoIterator<Item = &'a Point<2, N>>,
+        style: impl Display,
+    ) -> &mut Self
+    where
+        N: ToPrimitive + Copy + 'a,
+    {
+        let points = points.into_iter().map(|p| (coord(p.x()), coord(p.y()))).collect();
+        self.add_polyline(points, style)
+    }
+
+    fn add_polyline(&mut self, points: Vec<(f64, f64)>, style: impl Display) -> &mut Self {
+        let mut coords = String::new();
+        for (ix, &(x, y)) in points.iter().enumerate() {
+            self.include(x, y);
+            let separator = if ix == 0 { "" } else { " " };
+            write!(coords, "{separator}{x},{y}").unwrap();
+        }
+        self.elements.push(format!(
+            "<polyline points=\"{coords}\" fill=\"none\" style=\"{style}\"/>"
+        ));
+        self
+    }
+
+    pub fn line_segment<N>(&mut self, line: &LineSegment<2, N>, style: impl Display) -> &mut Self
+    where
+        N: ToPrimitive + Copy,
+    {
+        let (x1, y1) = (coord(line.start.x()), coord(line.start.y()));
+        let (x2, y2) = (coord(line.end.x()), coord(line.end.y()));
+        self.include(x1, y1);
+        self.include(x2, y2);
+        self.elements.push(format!(
+            "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" style=\"{style}\"/>"
+        ));
+        self
+    }
+
+    pub fn bounding_box<N>(&mut self, bounds: &BoundingBox<2, N>, style: impl Display) -> &mut Self
+    where
+        N: ToPrimitive + Copy,
+    {
+        let (x, y) = (coord(bounds.min_point().x()), coord(bounds.min_point().y()));
+        let (max_x, max_y) = (coord(bounds.max_point().x()), coord(bounds.max_point().y()));
+        self.include(x, y);
+        self.include(max_x, max_y);
+        self.elements.push(format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" style=\"{style}\"/>",
+            max_x - x,
+            max_y - y
+        ));
+        self
+    }
+
+    /// Writes the text centered in the grid cell
+    pub fn label(&mut self, location: Location, text: &str, style: impl Display) -> &mut Self {
+        let (x, y) = (location.x() as f64 + 0.5, location.y() as f64 + 0.5);
+        self.include(x - 0.5, y - 0.5);
+        self.include(x + 0.5, y + 0.5);
+        self.elements.push(format!(
+            "<text x=\"{x}\" y=\"{y}\" font-size=\"0.8\" text-anchor=\"middle\" \
+             dominant-baseline=\"central\" style=\"{style}\">{}</text>",
+            escape(text)
+        ));
+        self
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        let (min_x, min_y) = self.min.unwrap_or((0., 0.));
+        let (max_x, max_y) = self.max.unwrap_or((0., 0.));
+        let mut result = format!(
+            "<svg version=\"1.1\" viewBox=\"{} {} {} {}\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+            min_x - self.margin,
+            min_y - self.margin,
+            max_x - min_x + 2. * self.margin,
+            max_y - min_y + 2. * self.margin
+        );
+        for element in &self.elements {
+            result.push_str(element);
+            result.push('\n');
+        }
+        result.push_str("</svg>\n");
+        result
+    }
+
+    pub fn save(&self, filename: &str) {
+        std::fs::write(filename, self.to_svg_string()).expect("Expect saving to not be a problem");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Svg;
+    use crate::geometry::{BoundingBox, point2};
+    use crate::grid::Grid;
+    use crate::lines::LineSegment;
+    use nom::error::Error;
+    use nom_parse_trait::ParseFromExt;
+
+    #[test]
+    fn grid_with_path_and_label() {
+        let result: Result<Grid<char>, Error<_>> = Grid::parse_complete("##.\n#..");
+        let grid = result.unwrap();
+        let mut svg = Svg::new();
+        svg.grid(&grid, |&c| (c == '#').then_some("fill:black"))
+            .path(&[point2(2, 0), point2(2, 1), point2(1, 1)], "stroke:red")
+            .label(point2(1, 1), "<E>", "fill:blue");
+
+        assert_eq!(
+            "<svg version=\"1.1\" viewBox=\"0 0 3 2\" xmlns=\"http://www.w3.org/2000/svg\">\n\
+             <rect x=\"0\" y=\"0\" width=\"2\" height=\"1\" style=\"fill:black\"/>\n\
+             <rect x=\"0\" y=\"1\" width=\"1\" height=\"1\" style=\"fill:black\"/>\n\
+             <polyline points=\"2.5,0.5 2.5,1.5 1.5,1.5\" fill=\"none\" style=\"stroke:red\"/>\n\
+             <text x=\"1.5\" y=\"1.5\" font-size=\"0.8\" text-anchor=\"middle\" \
+             dominant-baseline=\"central\" style=\"fill:blue\">&lt;E&gt;</text>\n\
+             </svg>\n",
+            svg.to_svg_string()
+        );
+    }
+
+    #[test]
+    fn lines_and_boxes() {
+        let mut svg = Svg::new().margin(1.);
+        svg.line_segment(
+            &LineSegment::<2, i64> { start: point2(-5, 0), end: point2(5, 0) },
+            "stroke:black",
+        )
+        .bounding_box(&BoundingBox::from(point2(0, 3), point2(2, -1)), "fill:none")
+        .polyline(&[point2(0.5, 0.5), point2(1.5, 0.25)], "stroke:green");
+
+        assert_eq!(
+            "<svg version=\"1.1\" viewBox=\"-6 -2 12 6\" xmlns=\"http://www.w3.org/2000/svg\">\n\
+             <line x1=\"-5\" y1=\"0\" x2=\"5\" y2=\"0\" style=\"stroke:black\"/>\n\
+             <rect x=\"0\" y=\"-1\" width=\"2\" height=\"4\" style=\"fill:none\"/>\n\
+             <polyline points=\"0.5,0.5 1.5,0.25\" fill=\"none\" style=\"stroke:green\"/>\n\
+             </svg>\n",
+            svg.to_svg_string()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::grid::Grid;
 use std::fmt::Debug;
 
 #[inline]
@@ -14,6 +15,25 @@ where
     );
 }
 
+/// Asserts that two grids are equal, showing both grids next to each other with the different
+/// cells highlighted when they are not.
+#[track_caller]
+pub fn assert_grid<T>(expected: &Grid<T>, actual: &Grid<T>)
+where
+    T: PartialEq + Copy + Into<char>,
+{
+    if let Some(report) = expected.diff_report(actual) {
+        panic!("Grids are not equal: {report}");
+    }
+}
+
+#[macro_export]
+macro_rules! assert_grid_eq {
+    ($expected: expr, $actual: expr) => {
+        $crate::test_utils::assert_grid(&$expected, &$actual)
+    };
+}
+
 #[allow(clippy::crate_in_macro_def)] // This is the whole point, to use the call-site's crate
 #[macro_export]
 macro_rules! day_test {